@@ -0,0 +1,125 @@
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKLMNPQRSTUVXY";
+const COUNTRY_CODE: &str = "NQ";
+
+/// Converts a hex encoded address into its user friendly form, e.g. `NQ07 0000 ... 0000`.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::address;
+/// assert_eq!(
+///     address::to_user_friendly("0000000000000000000000000000000000000000").unwrap(),
+///     "NQ07 0000 0000 0000 0000 0000 0000 0000 0000"
+/// );
+/// ```
+pub fn to_user_friendly(hex_address: &str) -> Option<String> {
+    let bytes = hex::decode(hex_address).ok()?;
+    if bytes.len() != 20 {
+        return None;
+    }
+
+    let base32 = base32_encode(&bytes);
+    let check = 98 - iban_check(&format!("{}{}00", base32, COUNTRY_CODE))?;
+    let address = format!("{}{:02}{}", COUNTRY_CODE, check, base32);
+
+    Some(
+        address
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| String::from_utf8_lossy(chunk))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Converts a user friendly address into its hex encoded form.
+///
+/// Returns `None` if the address is malformed or its check digits do not match.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::address;
+/// assert_eq!(
+///     address::to_hex("NQ07 0000 0000 0000 0000 0000 0000 0000 0000").unwrap(),
+///     "0000000000000000000000000000000000000000"
+/// );
+/// ```
+pub fn to_hex(user_friendly: &str) -> Option<String> {
+    let address: String = user_friendly
+        .split_whitespace()
+        .collect::<String>()
+        .to_uppercase();
+    // Only ASCII letters and digits, which also makes the slices below fall on char boundaries.
+    if address.len() != 36
+        || !address.starts_with(COUNTRY_CODE)
+        || !address.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    if iban_check(&format!("{}{}", &address[4..], &address[..4]))? != 1 {
+        return None;
+    }
+
+    base32_decode(&address[4..]).map(hex::encode)
+}
+
+/// Returns whether the string is a valid user friendly or hex encoded address.
+pub fn is_valid(address: &str) -> bool {
+    to_hex(address).is_some() || to_user_friendly(address).is_some()
+}
+
+/// Computes the ISO 7064 mod 97 remainder, or `None` if `value` is not ASCII alphanumeric.
+fn iban_check(value: &str) -> Option<u32> {
+    let digits = value
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => Some(c.to_string()),
+            'A'..='Z' | 'a'..='z' => Some((u32::from(c.to_ascii_uppercase()) - 55).to_string()),
+            _ => None,
+        })
+        .collect::<Option<String>>()?;
+
+    Some(digits.as_bytes().chunks(6).fold(0, |remainder, chunk| {
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        format!("{}{}", remainder, chunk).parse::<u64>().unwrap() as u32 % 97
+    }))
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        result.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+
+    result
+}
+
+fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in value.bytes() {
+        let index = ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 5) | index;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(result)
+}
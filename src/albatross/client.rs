@@ -0,0 +1,1303 @@
+use jsonrpc::error::Error;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::primitives::*;
+use crate::metrics::RequestMetrics;
+use crate::protocol::{Capability, NodeInfo};
+use crate::transport::Transport;
+
+/// Client for the JSON-RPC of Nimiq 2.0 (Albatross) nodes, the proof-of-stake successor of the
+/// 1.x proof-of-work protocol spoken by the legacy [`Client`](crate::Client).
+///
+/// Albatross nodes wrap every result in `{"data": ..., "metadata": ...}`, the client returns
+/// the data. Requests go through the same transports and metrics as the legacy client.
+pub struct Client {
+    inner: crate::Client,
+}
+
+impl Client {
+    pub fn new(host: &str) -> Client {
+        Client {
+            inner: crate::Client::new(host),
+        }
+    }
+
+    pub fn new_with_credentials(host: &str, username: &str, password: &str) -> Client {
+        Client {
+            inner: crate::Client::new_with_credentials(host, username, password),
+        }
+    }
+
+    /// Creates a client that talks to a node on the same host over the Unix domain socket at
+    /// `path`.
+    #[cfg(unix)]
+    pub fn new_with_unix_socket<P: AsRef<std::path::Path>>(path: P) -> Client {
+        Client {
+            inner: crate::Client::new_with_unix_socket(path),
+        }
+    }
+
+    /// Creates a client that sends its requests through the given transport.
+    pub fn new_with_transport<T: Transport + 'static>(transport: T) -> Client {
+        Client {
+            inner: crate::Client::new_with_transport(transport),
+        }
+    }
+
+    /// Returns the call counts, error counts and latencies of the requests sent so far.
+    pub fn metrics(&self) -> &RequestMetrics {
+        self.inner.metrics()
+    }
+
+    /// Returns which protocol the node speaks, probed once and cached.
+    pub fn node_info(&self) -> Result<NodeInfo, Error> {
+        self.inner.node_info()
+    }
+
+    /// Fails with an [`Unsupported`](crate::protocol::Unsupported) error naming the node's
+    /// protocol if it lacks `capability`.
+    pub fn require(&self, capability: Capability) -> Result<(), Error> {
+        self.inner.require(capability)
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: &[Value]) -> Result<T, Error> {
+        self.inner
+            .call::<RpcResult<T, Value>>(method, params)
+            .map(|result| result.data)
+    }
+
+    /// Returns the number of the latest block.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The height of the head block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.block_number();
+    /// ```
+    pub fn block_number(&self) -> Result<u32, Error> {
+        self.call("getBlockNumber", &[])
+    }
+
+    /// Returns the number of the current batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The batch the head block belongs to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.batch_number();
+    /// ```
+    pub fn batch_number(&self) -> Result<u32, Error> {
+        self.call("getBatchNumber", &[])
+    }
+
+    /// Returns the number of the current epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The epoch the head block belongs to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.epoch_number();
+    /// ```
+    pub fn epoch_number(&self) -> Result<u32, Error> {
+        self.call("getEpochNumber", &[])
+    }
+
+    /// Returns a micro or macro block by its number.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number`: Height of the block.
+    /// * `include_body`: If `true` the block includes its transactions.
+    ///
+    /// # Returns
+    ///
+    /// The block, see [`BlockKind`] for the fields of each block type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_block_by_number(882418, false);
+    /// ```
+    pub fn get_block_by_number(
+        &self,
+        block_number: u32,
+        include_body: bool,
+    ) -> Result<Block, Error> {
+        let params = &[
+            serde_json::to_value(block_number)?,
+            serde_json::to_value(include_body)?,
+        ];
+        self.call("getBlockByNumber", params)
+    }
+
+    /// Returns a micro or macro block by its hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash`: Hash of the block.
+    /// * `include_body`: If `true` the block includes its transactions.
+    ///
+    /// # Returns
+    ///
+    /// The block, see [`BlockKind`] for the fields of each block type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_block_by_hash("14c91f6d6f3a0b62271e546bb09461231ab7e4d1ddc2c3e1b93de52d48a1da87", false);
+    /// ```
+    pub fn get_block_by_hash(&self, hash: &str, include_body: bool) -> Result<Block, Error> {
+        let params = &[
+            serde_json::to_value(hash)?,
+            serde_json::to_value(include_body)?,
+        ];
+        self.call("getBlockByHash", params)
+    }
+
+    /// Returns the head block.
+    ///
+    /// # Arguments
+    ///
+    /// * `include_body`: If `true` the block includes its transactions.
+    ///
+    /// # Returns
+    ///
+    /// The head block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_latest_block(false);
+    /// ```
+    pub fn get_latest_block(&self, include_body: bool) -> Result<Block, Error> {
+        let params = &[serde_json::to_value(include_body)?];
+        self.call("getLatestBlock", params)
+    }
+
+    /// Returns the slot that produced or will produce the block at the given height.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number`: Height of the block.
+    /// * `offset`: View change offset, the one of the block itself if `None`.
+    ///
+    /// # Returns
+    ///
+    /// The slot with its validator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_slot_at(882418, None);
+    /// ```
+    pub fn get_slot_at(&self, block_number: u32, offset: Option<u32>) -> Result<Slot, Error> {
+        let params = &[
+            serde_json::to_value(block_number)?,
+            serde_json::to_value(offset)?,
+        ];
+        self.call("getSlotAt", params)
+    }
+
+    /// Returns a transaction by its hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash`: Hash of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// The transaction, with its block number if it is included in a block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_transaction_by_hash("465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554");
+    /// ```
+    pub fn get_transaction_by_hash(&self, hash: &str) -> Result<Transaction, Error> {
+        let params = &[serde_json::to_value(hash)?];
+        self.call("getTransactionByHash", params)
+    }
+
+    /// Returns the transactions of a block, including the inherents of macro blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number`: Height of the block.
+    ///
+    /// # Returns
+    ///
+    /// The transactions of the block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_transactions_by_block_number(882418);
+    /// ```
+    pub fn get_transactions_by_block_number(
+        &self,
+        block_number: u32,
+    ) -> Result<Vec<Transaction>, Error> {
+        let params = &[serde_json::to_value(block_number)?];
+        self.call("getTransactionsByBlockNumber", params)
+    }
+
+    /// Returns the transactions of all blocks of a batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_number`: Number of the batch.
+    ///
+    /// # Returns
+    ///
+    /// The transactions of the batch.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_transactions_by_batch_number(1234);
+    /// ```
+    pub fn get_transactions_by_batch_number(
+        &self,
+        batch_number: u32,
+    ) -> Result<Vec<Transaction>, Error> {
+        let params = &[serde_json::to_value(batch_number)?];
+        self.call("getTransactionsByBatchNumber", params)
+    }
+
+    /// Returns the transactions sent or received by an address, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address.
+    /// * `max`: Maximum number of transactions, up to the node's limit if `None`.
+    ///
+    /// # Returns
+    ///
+    /// The transactions of the address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_transactions_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN", Some(10));
+    /// ```
+    pub fn get_transactions_by_address(
+        &self,
+        address: &str,
+        max: Option<u16>,
+    ) -> Result<Vec<Transaction>, Error> {
+        let params = &[serde_json::to_value(address)?, serde_json::to_value(max)?];
+        self.call("getTransactionsByAddress", params)
+    }
+
+    /// Returns the hashes of the transactions sent or received by an address, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address.
+    /// * `max`: Maximum number of hashes, up to the node's limit if `None`.
+    ///
+    /// # Returns
+    ///
+    /// The transaction hashes of the address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_transaction_hashes_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN", None);
+    /// ```
+    pub fn get_transaction_hashes_by_address(
+        &self,
+        address: &str,
+        max: Option<u16>,
+    ) -> Result<Vec<String>, Error> {
+        let params = &[serde_json::to_value(address)?, serde_json::to_value(max)?];
+        self.call("getTransactionHashesByAddress", params)
+    }
+
+    /// Returns the account of an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address.
+    ///
+    /// # Returns
+    ///
+    /// The account, a basic account without balance for unknown addresses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_account_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// ```
+    pub fn get_account_by_address(&self, address: &str) -> Result<Account, Error> {
+        let params = &[serde_json::to_value(address)?];
+        self.call("getAccountByAddress", params)
+    }
+
+    /// Returns whether the node has established consensus with the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// `true` once the node is in sync.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.is_consensus_established();
+    /// ```
+    pub fn is_consensus_established(&self) -> Result<bool, Error> {
+        self.call("isConsensusEstablished", &[])
+    }
+
+    /// Returns the peer id of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The libp2p peer id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_peer_id();
+    /// ```
+    pub fn get_peer_id(&self) -> Result<String, Error> {
+        self.call("getPeerId", &[])
+    }
+
+    /// Returns the number of connected peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The number of peers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_peer_count();
+    /// ```
+    pub fn get_peer_count(&self) -> Result<u32, Error> {
+        self.call("getPeerCount", &[])
+    }
+
+    /// Returns the ids of the connected peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The peer ids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_peer_list();
+    /// ```
+    pub fn get_peer_list(&self) -> Result<Vec<String>, Error> {
+        self.call("getPeerList", &[])
+    }
+
+    /// Returns the protocol constants of the network, such as the epoch length.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The policy constants.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_policy_constants();
+    /// ```
+    pub fn get_policy_constants(&self) -> Result<PolicyConstants, Error> {
+        self.call("getPolicyConstants", &[])
+    }
+
+    /// Returns the number of transactions in the mempool, per fee per byte bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The mempool summary.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.mempool();
+    /// ```
+    pub fn mempool(&self) -> Result<Mempool, Error> {
+        self.call("mempool", &[])
+    }
+
+    /// Sends a signed transaction to the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_transaction`: Hex encoded signed transaction.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.push_transaction("010000...abcdef");
+    /// ```
+    pub fn push_transaction(&self, raw_transaction: &str) -> Result<String, Error> {
+        let params = &[serde_json::to_value(raw_transaction)?];
+        self.call("pushTransaction", params)
+    }
+
+    /// Creates, signs and sends a basic transaction from an unlocked account of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet`: Address of an unlocked account of the node.
+    /// * `recipient`: User friendly address of the recipient.
+    /// * `value`: Value in Luna.
+    /// * `fee`: Fee in Luna.
+    /// * `validity_start_height`: Height from which the transaction is valid.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::ValidityStartHeight;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.send_basic_transaction("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN", "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2", 100000, 0, ValidityStartHeight::Relative(0));
+    /// ```
+    pub fn send_basic_transaction(
+        &self,
+        wallet: &str,
+        recipient: &str,
+        value: u64,
+        fee: u64,
+        validity_start_height: ValidityStartHeight,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(wallet)?,
+            serde_json::to_value(recipient)?,
+            serde_json::to_value(value)?,
+            serde_json::to_value(fee)?,
+            serde_json::to_value(validity_start_height)?,
+        ];
+        self.call("sendBasicTransaction", params)
+    }
+
+    /// Returns the validators that hold slots in the current epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// The active validators with their stake.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_active_validators();
+    /// ```
+    pub fn get_active_validators(&self) -> Result<Vec<Validator>, Error> {
+        self.call("getActiveValidators", &[])
+    }
+
+    /// Returns a validator by its address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address of the validator.
+    ///
+    /// # Returns
+    ///
+    /// The validator with its keys and stake.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_validator_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// ```
+    pub fn get_validator_by_address(&self, address: &str) -> Result<Validator, Error> {
+        let params = &[serde_json::to_value(address)?];
+        self.call("getValidatorByAddress", params)
+    }
+
+    /// Returns the stakers that delegate to a validator.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address of the validator.
+    ///
+    /// # Returns
+    ///
+    /// The delegating stakers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_stakers_by_validator_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// ```
+    pub fn get_stakers_by_validator_address(&self, address: &str) -> Result<Vec<Staker>, Error> {
+        let params = &[serde_json::to_value(address)?];
+        self.call("getStakersByValidatorAddress", params)
+    }
+
+    /// Returns a staker by its address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: User friendly address of the staker.
+    ///
+    /// # Returns
+    ///
+    /// The staker with its delegation and balances.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_staker_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// ```
+    pub fn get_staker_by_address(&self, address: &str) -> Result<Staker, Error> {
+        let params = &[serde_json::to_value(address)?];
+        self.call("getStakerByAddress", params)
+    }
+
+    /// Returns the epoch a block belongs to.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number`: Height of the block.
+    ///
+    /// # Returns
+    ///
+    /// The epoch number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_epoch_at(882418);
+    /// ```
+    pub fn get_epoch_at(&self, block_number: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(block_number)?];
+        self.call("getEpochAt", params)
+    }
+
+    /// Returns the batch a block belongs to.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number`: Height of the block.
+    ///
+    /// # Returns
+    ///
+    /// The batch number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_batch_at(882418);
+    /// ```
+    pub fn get_batch_at(&self, block_number: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(block_number)?];
+        self.call("getBatchAt", params)
+    }
+
+    /// Returns the number of the first block of an epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch`: Number of the epoch.
+    ///
+    /// # Returns
+    ///
+    /// The block number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_first_block_of(80);
+    /// ```
+    pub fn get_first_block_of(&self, epoch: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(epoch)?];
+        self.call("getFirstBlockOf", params)
+    }
+
+    /// Returns the number of the election block that ends an epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch`: Number of the epoch.
+    ///
+    /// # Returns
+    ///
+    /// The block number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_election_block_of(80);
+    /// ```
+    pub fn get_election_block_of(&self, epoch: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(epoch)?];
+        self.call("getElectionBlockOf", params)
+    }
+
+    /// Returns the number of the first block of a batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch`: Number of the batch.
+    ///
+    /// # Returns
+    ///
+    /// The block number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_first_block_of_batch(57600);
+    /// ```
+    pub fn get_first_block_of_batch(&self, batch: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(batch)?];
+        self.call("getFirstBlockOfBatch", params)
+    }
+
+    /// Returns the number of the macro block that ends a batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch`: Number of the batch.
+    ///
+    /// # Returns
+    ///
+    /// The block number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.get_macro_block_of(57600);
+    /// ```
+    pub fn get_macro_block_of(&self, batch: u32) -> Result<u32, Error> {
+        let params = &[serde_json::to_value(batch)?];
+        self.call("getMacroBlockOf", params)
+    }
+
+    /// Creates and signs a transaction that creates a staker, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`NewStakerTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = NewStakerTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     delegation: Some("NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2".to_owned()),
+    ///     value: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_new_staker_transaction(&transaction);
+    /// ```
+    pub fn create_new_staker_transaction(
+        &self,
+        transaction: &NewStakerTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.delegation)?,
+            serde_json::to_value(transaction.value)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createNewStakerTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that creates a staker.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`NewStakerTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = NewStakerTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     delegation: Some("NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2".to_owned()),
+    ///     value: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_new_staker_transaction(&transaction);
+    /// ```
+    pub fn send_new_staker_transaction(
+        &self,
+        transaction: &NewStakerTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.delegation)?,
+            serde_json::to_value(transaction.value)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendNewStakerTransaction", params)
+    }
+
+    /// Creates and signs a transaction that changes the delegation of a staker, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`UpdateStakerTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = UpdateStakerTransaction {
+    ///     sender_wallet: None,
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     new_delegation: None,
+    ///     reactivate_all_stake: false,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_update_staker_transaction(&transaction);
+    /// ```
+    pub fn create_update_staker_transaction(
+        &self,
+        transaction: &UpdateStakerTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.new_delegation)?,
+            serde_json::to_value(transaction.reactivate_all_stake)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createUpdateStakerTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that changes the delegation of a staker.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`UpdateStakerTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = UpdateStakerTransaction {
+    ///     sender_wallet: None,
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     new_delegation: None,
+    ///     reactivate_all_stake: false,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_update_staker_transaction(&transaction);
+    /// ```
+    pub fn send_update_staker_transaction(
+        &self,
+        transaction: &UpdateStakerTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.new_delegation)?,
+            serde_json::to_value(transaction.reactivate_all_stake)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendUpdateStakerTransaction", params)
+    }
+
+    /// Creates and signs a transaction that sets the active stake of a staker, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`SetActiveStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = SetActiveStakeTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     new_active_balance: 0,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_set_active_stake_transaction(&transaction);
+    /// ```
+    pub fn create_set_active_stake_transaction(
+        &self,
+        transaction: &SetActiveStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(transaction.new_active_balance)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createSetActiveStakeTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that sets the active stake of a staker.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`SetActiveStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = SetActiveStakeTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     new_active_balance: 0,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_set_active_stake_transaction(&transaction);
+    /// ```
+    pub fn send_set_active_stake_transaction(
+        &self,
+        transaction: &SetActiveStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(transaction.new_active_balance)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendSetActiveStakeTransaction", params)
+    }
+
+    /// Creates and signs a transaction that retires inactive stake, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`RetireStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = RetireStakeTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     retire_stake: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_retire_stake_transaction(&transaction);
+    /// ```
+    pub fn create_retire_stake_transaction(
+        &self,
+        transaction: &RetireStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(transaction.retire_stake)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createRetireStakeTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that retires inactive stake.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`RetireStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = RetireStakeTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     retire_stake: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_retire_stake_transaction(&transaction);
+    /// ```
+    pub fn send_retire_stake_transaction(
+        &self,
+        transaction: &RetireStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(transaction.retire_stake)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendRetireStakeTransaction", params)
+    }
+
+    /// Creates and signs a transaction that pays out retired stake, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`RemoveStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = RemoveStakeTransaction {
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     recipient: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     value: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_remove_stake_transaction(&transaction);
+    /// ```
+    pub fn create_remove_stake_transaction(
+        &self,
+        transaction: &RemoveStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.recipient)?,
+            serde_json::to_value(transaction.value)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createRemoveStakeTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that pays out retired stake.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`RemoveStakeTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = RemoveStakeTransaction {
+    ///     staker_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     recipient: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     value: 100_000_000,
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_remove_stake_transaction(&transaction);
+    /// ```
+    pub fn send_remove_stake_transaction(
+        &self,
+        transaction: &RemoveStakeTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.staker_wallet)?,
+            serde_json::to_value(&transaction.recipient)?,
+            serde_json::to_value(transaction.value)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendRemoveStakeTransaction", params)
+    }
+
+    /// Creates and signs a transaction that registers a validator, without sending it.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`NewValidatorTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded signed transaction, ready for [`Client::push_transaction`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = NewValidatorTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     validator_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     signing_secret_key: "00".repeat(32),
+    ///     voting_secret_key: "00".repeat(95),
+    ///     reward_address: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     signal_data: String::new(),
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.create_new_validator_transaction(&transaction);
+    /// ```
+    pub fn create_new_validator_transaction(
+        &self,
+        transaction: &NewValidatorTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.validator_wallet)?,
+            serde_json::to_value(&transaction.signing_secret_key)?,
+            serde_json::to_value(&transaction.voting_secret_key)?,
+            serde_json::to_value(&transaction.reward_address)?,
+            serde_json::to_value(&transaction.signal_data)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("createNewValidatorTransaction", params)
+    }
+
+    /// Creates, signs and sends a transaction that registers a validator.
+    ///
+    /// The wallets have to be unlocked accounts of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction`: The [`NewValidatorTransaction`] to create.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::albatross::Client;
+    /// use nimiq_rpc::albatross::primitives::*;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let transaction = NewValidatorTransaction {
+    ///     sender_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     validator_wallet: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     signing_secret_key: "00".repeat(32),
+    ///     voting_secret_key: "00".repeat(95),
+    ///     reward_address: "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN".to_owned(),
+    ///     signal_data: String::new(),
+    ///     fee: 0,
+    ///     validity_start_height: ValidityStartHeight::Relative(0),
+    /// };
+    /// let result = client.send_new_validator_transaction(&transaction);
+    /// ```
+    pub fn send_new_validator_transaction(
+        &self,
+        transaction: &NewValidatorTransaction,
+    ) -> Result<String, Error> {
+        let params = &[
+            serde_json::to_value(&transaction.sender_wallet)?,
+            serde_json::to_value(&transaction.validator_wallet)?,
+            serde_json::to_value(&transaction.signing_secret_key)?,
+            serde_json::to_value(&transaction.voting_secret_key)?,
+            serde_json::to_value(&transaction.reward_address)?,
+            serde_json::to_value(&transaction.signal_data)?,
+            serde_json::to_value(transaction.fee)?,
+            serde_json::to_value(transaction.validity_start_height)?,
+        ];
+        self.call("sendNewValidatorTransaction", params)
+    }
+}
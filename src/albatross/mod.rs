@@ -0,0 +1,4 @@
+mod client;
+pub mod primitives;
+
+pub use self::client::Client;
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResult<T, M = Option<BlockchainState>> {
+    pub data: T,
+    pub metadata: M,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainState {
+    pub block_number: u32,
+    pub block_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    pub hash: String,
+    pub size: u32,
+    pub batch: u32,
+    pub epoch: u32,
+    pub network: String,
+    pub version: u16,
+    pub number: u32,
+    pub timestamp: u64,
+    pub parent_hash: String,
+    pub seed: String,
+    pub extra_data: Vec<u8>,
+    pub state_hash: String,
+    pub body_hash: String,
+    pub history_hash: String,
+    pub transactions: Option<Vec<Transaction>>,
+    #[serde(flatten)]
+    pub kind: BlockKind,
+}
+
+impl Block {
+    pub fn is_macro(&self) -> bool {
+        matches!(self.kind, BlockKind::Macro(_))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum BlockKind {
+    Micro(MicroBlock),
+    Macro(MacroBlock),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicroBlock {
+    pub producer: Slot,
+    pub fork_proofs: Option<Vec<Value>>,
+    pub justification: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroBlock {
+    pub is_election_block: bool,
+    pub parent_election_hash: String,
+    pub interlink: Option<Vec<String>>,
+    pub slots: Option<Vec<Slots>>,
+    pub lost_reward_set: Option<Vec<u16>>,
+    pub disabled_set: Option<Vec<u16>>,
+    pub justification: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Slot {
+    pub slot_number: u16,
+    pub validator: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Slots {
+    pub first_slot_number: u16,
+    pub num_slots: u16,
+    pub validator: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub hash: String,
+    pub block_number: Option<u32>,
+    pub timestamp: Option<u64>,
+    pub confirmations: Option<u32>,
+    pub size: u32,
+    #[serde(default)]
+    pub related_addresses: Vec<String>,
+    pub from: String,
+    pub from_type: u8,
+    pub to: String,
+    pub to_type: u8,
+    pub value: u64,
+    pub fee: u64,
+    pub sender_data: String,
+    pub recipient_data: String,
+    pub flags: u8,
+    pub validity_start_height: u32,
+    pub proof: String,
+    pub network_id: u8,
+    pub execution_result: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Account {
+    #[serde(rename_all = "camelCase")]
+    Basic { address: String, balance: u64 },
+    #[serde(rename_all = "camelCase")]
+    Vesting {
+        address: String,
+        balance: u64,
+        owner: String,
+        vesting_start: u64,
+        vesting_time_step: u64,
+        vesting_step_amount: u64,
+        vesting_total_amount: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Htlc {
+        address: String,
+        balance: u64,
+        sender: String,
+        recipient: String,
+        hash_root: String,
+        hash_count: u8,
+        timeout: u64,
+        total_amount: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Staking { address: String, balance: u64 },
+}
+
+impl Account {
+    pub fn address(&self) -> &str {
+        match self {
+            Account::Basic { address, .. }
+            | Account::Vesting { address, .. }
+            | Account::Htlc { address, .. }
+            | Account::Staking { address, .. } => address,
+        }
+    }
+
+    pub fn balance(&self) -> u64 {
+        match self {
+            Account::Basic { balance, .. }
+            | Account::Vesting { balance, .. }
+            | Account::Htlc { balance, .. }
+            | Account::Staking { balance, .. } => *balance,
+        }
+    }
+}
+
+/// Events of a block for the addresses of a log subscription.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum BlockLog {
+    #[serde(rename_all = "camelCase")]
+    AppliedBlock {
+        #[serde(default)]
+        inherents: Vec<Value>,
+        block_hash: String,
+        block_number: u32,
+        timestamp: u64,
+        #[serde(default)]
+        transactions: Vec<TransactionLog>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RevertedBlock {
+        #[serde(default)]
+        inherents: Vec<Value>,
+        block_hash: String,
+        block_number: u32,
+        #[serde(default)]
+        transactions: Vec<TransactionLog>,
+    },
+}
+
+impl BlockLog {
+    pub fn transactions(&self) -> &[TransactionLog] {
+        match self {
+            BlockLog::AppliedBlock { transactions, .. }
+            | BlockLog::RevertedBlock { transactions, .. } => transactions,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionLog {
+    pub hash: String,
+    #[serde(default)]
+    pub logs: Vec<Value>,
+    pub failed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyConstants {
+    pub staking_contract_address: String,
+    pub coinbase_address: String,
+    pub transaction_validity_window: u32,
+    pub max_size_micro_body: u32,
+    pub version: u16,
+    pub slots: u16,
+    pub blocks_per_batch: u32,
+    pub batches_per_epoch: u16,
+    pub blocks_per_epoch: u32,
+    pub validator_deposit: u64,
+    pub minimum_stake: u64,
+    pub total_supply: u64,
+    pub block_separation_time: u64,
+    pub jail_epochs: u32,
+    pub genesis_block_number: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mempool {
+    pub total: u32,
+    pub buckets: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validator {
+    pub address: String,
+    pub signing_key: String,
+    pub voting_key: String,
+    pub reward_address: String,
+    pub signal_data: Option<String>,
+    pub balance: u64,
+    pub num_stakers: u64,
+    pub retired: bool,
+    pub inactivity_flag: Option<u32>,
+    pub jailed_from: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Staker {
+    pub address: String,
+    pub balance: u64,
+    pub delegation: Option<String>,
+    #[serde(default)]
+    pub inactive_balance: u64,
+    pub inactive_from: Option<u32>,
+    #[serde(default)]
+    pub retired_balance: u64,
+}
+
+impl Staker {
+    /// Returns the whole stake, active, inactive and retired.
+    pub fn total_balance(&self) -> u64 {
+        self.balance + self.inactive_balance + self.retired_balance
+    }
+}
+
+/// Height from which a transaction is valid, either a block number or an offset from the head.
+///
+/// Sent to the node as `"882418"` or `"+10"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidityStartHeight {
+    Absolute(u32),
+    Relative(u32),
+}
+
+impl Default for ValidityStartHeight {
+    fn default() -> ValidityStartHeight {
+        ValidityStartHeight::Relative(0)
+    }
+}
+
+impl Serialize for ValidityStartHeight {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ValidityStartHeight::Absolute(height) => serializer.collect_str(height),
+            ValidityStartHeight::Relative(offset) => {
+                serializer.collect_str(&format!("+{}", offset))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ValidityStartHeight {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let height = String::deserialize(deserializer)?;
+        match height.strip_prefix('+') {
+            Some(offset) => offset.parse().map(ValidityStartHeight::Relative),
+            None => height.parse().map(ValidityStartHeight::Absolute),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Stakes coins from `sender_wallet` for a new staker, optionally delegated to a validator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewStakerTransaction {
+    pub sender_wallet: String,
+    pub staker_wallet: String,
+    pub delegation: Option<String>,
+    pub value: u64,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
+
+/// Changes the delegation of a staker, signed by `staker_wallet` and paid by `sender_wallet`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStakerTransaction {
+    /// The staker pays the fee if `None`.
+    pub sender_wallet: Option<String>,
+    pub staker_wallet: String,
+    pub new_delegation: Option<String>,
+    pub reactivate_all_stake: bool,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
+
+/// Sets the active balance of a staker, moving the rest of its active stake to its inactive
+/// balance or reactivating inactive stake.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetActiveStakeTransaction {
+    pub sender_wallet: String,
+    pub staker_wallet: String,
+    pub new_active_balance: u64,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
+
+/// Moves inactive stake of a staker to its retired balance once it can be released.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetireStakeTransaction {
+    pub sender_wallet: String,
+    pub staker_wallet: String,
+    pub retire_stake: u64,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
+
+/// Removes retired stake from a staker and pays it out to `recipient`.
+///
+/// Active stake has to be deactivated with a [`SetActiveStakeTransaction`] and retired with a
+/// [`RetireStakeTransaction`] first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveStakeTransaction {
+    pub staker_wallet: String,
+    pub recipient: String,
+    pub value: u64,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
+
+/// Registers a validator, paying the deposit from `sender_wallet`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewValidatorTransaction {
+    pub sender_wallet: String,
+    pub validator_wallet: String,
+    /// Hex encoded Schnorr secret key.
+    pub signing_secret_key: String,
+    /// Hex encoded BLS secret key.
+    pub voting_secret_key: String,
+    pub reward_address: String,
+    /// Hex encoded, empty for no signal data.
+    pub signal_data: String,
+    pub fee: u64,
+    pub validity_start_height: ValidityStartHeight,
+}
@@ -0,0 +1,58 @@
+//! Serves Prometheus metrics about the health of a Nimiq node.
+//!
+//! ```text
+//! nimiq-rpc-exporter --host http://127.0.0.1:8648 --listen 0.0.0.0:9688
+//! ```
+
+use std::process;
+
+use clap::Parser;
+use nimiq_rpc::exporter::Exporter;
+use nimiq_rpc::Client;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nimiq-rpc-exporter",
+    version,
+    about = "Export Nimiq node metrics for Prometheus"
+)]
+struct Cli {
+    /// URL of the node's RPC server.
+    #[arg(long, env = "NIMIQ_RPC_HOST", default_value = "http://127.0.0.1:8648")]
+    host: String,
+
+    /// Username for RPC authentication.
+    #[arg(long, env = "NIMIQ_RPC_USER", requires = "password")]
+    user: Option<String>,
+
+    /// Password for RPC authentication.
+    #[arg(
+        long,
+        env = "NIMIQ_RPC_PASSWORD",
+        hide_env_values = true,
+        requires = "user"
+    )]
+    password: Option<String>,
+
+    /// Address to serve the metrics on.
+    #[arg(long, env = "NIMIQ_EXPORTER_LISTEN", default_value = "0.0.0.0:9688")]
+    listen: String,
+
+    /// Prefix of all metric names.
+    #[arg(long, default_value = "nimiq")]
+    namespace: String,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let client = match (&cli.user, &cli.password) {
+        (Some(user), Some(password)) => Client::new_with_credentials(&cli.host, user, password),
+        _ => Client::new(&cli.host),
+    };
+
+    let exporter = Exporter::new(client).namespace(&cli.namespace);
+    if let Err(err) = exporter.listen(&cli.listen) {
+        eprintln!("error: cannot serve metrics on {}: {}", cli.listen, err);
+        process::exit(1);
+    }
+}
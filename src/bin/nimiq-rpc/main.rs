@@ -0,0 +1,341 @@
+//! Command-line access to every method of the Nimiq RPC client.
+//!
+//! ```text
+//! nimiq-rpc --host http://127.0.0.1:8648 get-balance "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN"
+//! ```
+//!
+//! The connection can also be configured through the `NIMIQ_RPC_HOST`, `NIMIQ_RPC_USER` and
+//! `NIMIQ_RPC_PASSWORD` environment variables. Run `nimiq-rpc shell` for an interactive shell.
+
+mod output;
+mod shell;
+
+use std::fmt;
+use std::process;
+
+use clap::{Parser, Subcommand};
+use jsonrpc::error::Error;
+use nimiq_rpc::primitives::OutgoingTransaction;
+use nimiq_rpc::Client;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::output::Format;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "nimiq-rpc",
+    version,
+    about = "Talk to a Nimiq node over JSON-RPC"
+)]
+struct Cli {
+    /// URL of the node's RPC server.
+    #[arg(long, env = "NIMIQ_RPC_HOST", default_value = "http://127.0.0.1:8648")]
+    host: String,
+
+    /// Username for RPC authentication.
+    #[arg(long, env = "NIMIQ_RPC_USER", requires = "password")]
+    user: Option<String>,
+
+    /// Password for RPC authentication.
+    #[arg(
+        long,
+        env = "NIMIQ_RPC_PASSWORD",
+        hide_env_values = true,
+        requires = "user"
+    )]
+    password: Option<String>,
+
+    /// How results are printed.
+    #[arg(
+        long,
+        short,
+        env = "NIMIQ_RPC_OUTPUT",
+        value_enum,
+        default_value = "table"
+    )]
+    output: Format,
+
+    /// Print amounts in Luna instead of NIM.
+    #[arg(long, global = true)]
+    luna: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Amounts passed as arguments are in NIM and may have up to five decimals.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List the accounts owned by the node.
+    Accounts,
+    /// Print the height of the head block.
+    BlockNumber,
+    /// Print the consensus state of the node.
+    Consensus,
+    /// Create a new account in the node's wallet.
+    CreateAccount,
+    /// Create and sign a transaction without sending it.
+    CreateRawTransaction {
+        from: String,
+        to: String,
+        #[arg(value_parser = output::parse_nim)]
+        value: u64,
+        #[arg(value_parser = output::parse_nim, default_value = "0")]
+        fee: u64,
+    },
+    /// Print the details of an account.
+    GetAccount { address: String },
+    /// Print the balance of an account.
+    GetBalance { address: String },
+    /// Print a block by its hash.
+    GetBlockByHash {
+        hash: String,
+        /// Include full transactions instead of their hashes.
+        #[arg(long)]
+        full: bool,
+    },
+    /// Print a block by its height.
+    GetBlockByNumber {
+        number: u64,
+        /// Include full transactions instead of their hashes.
+        #[arg(long)]
+        full: bool,
+    },
+    /// Print a template for the next block to mine.
+    GetBlockTemplate,
+    /// Print the number of transactions in a block.
+    GetBlockTransactionCountByHash { hash: String },
+    /// Print the number of transactions in a block.
+    GetBlockTransactionCountByNumber { number: u64 },
+    /// Print a transaction by its position in a block.
+    GetTransactionByBlockHashAndIndex { hash: String, index: u64 },
+    /// Print a transaction by its position in a block.
+    GetTransactionByBlockNumberAndIndex { number: u64, index: u16 },
+    /// Print a transaction by its hash.
+    GetTransactionByHash { hash: String },
+    /// Print the receipt of a transaction.
+    GetTransactionReceipt { hash: String },
+    /// List the latest transactions of an address.
+    GetTransactionsByAddress {
+        address: String,
+        /// Maximum number of transactions.
+        #[arg(long, default_value = "1000")]
+        amount: u16,
+    },
+    /// Print the current mining work.
+    GetWork,
+    /// Print the node's hashrate.
+    Hashrate,
+    /// Set the log level of the node.
+    Log { tag: String, level: String },
+    /// Print the mempool transaction counts per fee bucket.
+    Mempool,
+    /// List the transactions in the mempool.
+    MempoolContent {
+        /// Include full transactions instead of their hashes.
+        #[arg(long)]
+        full: bool,
+    },
+    /// Print the address mining rewards are sent to.
+    MinerAddress,
+    /// Print or update the number of mining threads.
+    MinerThreads { threads: Option<u16> },
+    /// Print or update the minimum fee per byte.
+    MinFeePerByte { fee: Option<u32> },
+    /// Print whether the node is mining.
+    Mining,
+    /// Print the number of connected peers.
+    PeerCount,
+    /// List the peers known to the node.
+    PeerList,
+    /// Print or update the state of a peer.
+    PeerState {
+        address: String,
+        /// One of `connect`, `disconnect`, `ban` or `unban`.
+        set: Option<String>,
+    },
+    /// Print the confirmed balance of the mining pool.
+    PoolConfirmedBalance,
+    /// Print the connection state of the mining pool.
+    PoolConnectionState,
+    /// Send a signed transaction.
+    SendRawTransaction { transaction: String },
+    /// Send a transaction from an account of the node's wallet.
+    SendTransaction {
+        from: String,
+        to: String,
+        #[arg(value_parser = output::parse_nim)]
+        value: u64,
+        #[arg(value_parser = output::parse_nim, default_value = "0")]
+        fee: u64,
+    },
+    /// Submit a mined block.
+    SubmitBlock { block: String },
+    /// Print the sync status of the node.
+    Syncing,
+    /// Start an interactive shell.
+    Shell,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let client = match (&cli.user, &cli.password) {
+        (Some(user), Some(password)) => Client::new_with_credentials(&cli.host, user, password),
+        _ => Client::new(&cli.host),
+    };
+
+    if let Command::Shell = cli.command {
+        shell::Shell::new(client, cli.output, cli.luna).run();
+        return;
+    }
+
+    let is_amount = cli.command.is_amount();
+    let mut value = match run(&client, cli.command) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    };
+
+    if !cli.luna {
+        output::format_result(&mut value, is_amount);
+    }
+    output::print(&value, cli.output);
+}
+
+impl Command {
+    /// Returns whether the command's result is a single amount in Luna.
+    fn is_amount(&self) -> bool {
+        matches!(
+            self,
+            Command::GetBalance { .. } | Command::PoolConfirmedBalance
+        )
+    }
+}
+
+/// Why a command failed.
+enum CliError {
+    /// The request failed or the node answered with an error.
+    Client(Error),
+    /// The command cannot be run as given.
+    Invalid(&'static str),
+}
+
+impl From<Error> for CliError {
+    fn from(err: Error) -> CliError {
+        CliError::Client(err)
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(err: serde_json::Error) -> CliError {
+        CliError::Client(Error::Json(err))
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Client(Error::Rpc(err)) => write!(f, "{} ({})", err.message, err.code),
+            CliError::Client(err) => write!(f, "{}", err),
+            CliError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+fn run(client: &Client, command: Command) -> Result<Value, CliError> {
+    match command {
+        Command::Accounts => json(client.accounts()),
+        Command::BlockNumber => json(client.block_number()),
+        Command::Consensus => json(client.consensus()),
+        Command::CreateAccount => json(client.create_account()),
+        Command::CreateRawTransaction {
+            from,
+            to,
+            value,
+            fee,
+        } => json(client.create_raw_transaction(&outgoing(&from, &to, value, fee)?)),
+        Command::GetAccount { address } => json(client.get_account(&address)),
+        Command::GetBalance { address } => json(client.get_balance(&address)),
+        Command::GetBlockByHash { hash, full } => json(client.get_block_by_hash(&hash, full)),
+        Command::GetBlockByNumber { number, full } => {
+            json(client.get_block_by_number(number, full))
+        }
+        Command::GetBlockTemplate => json(client.get_block_template()),
+        Command::GetBlockTransactionCountByHash { hash } => {
+            json(client.get_block_transaction_count_by_hash(&hash))
+        }
+        Command::GetBlockTransactionCountByNumber { number } => {
+            json(client.get_block_transaction_count_by_number(number))
+        }
+        Command::GetTransactionByBlockHashAndIndex { hash, index } => {
+            json(client.get_transaction_by_block_hash_and_index(&hash, index))
+        }
+        Command::GetTransactionByBlockNumberAndIndex { number, index } => {
+            json(client.get_transaction_by_block_number_and_index(number, index))
+        }
+        Command::GetTransactionByHash { hash } => json(client.get_transaction_by_hash(&hash)),
+        Command::GetTransactionReceipt { hash } => json(client.get_transaction_receipt(&hash)),
+        Command::GetTransactionsByAddress { address, amount } => {
+            json(client.get_transactions_by_address(&address, amount))
+        }
+        Command::GetWork => json(client.get_work()),
+        Command::Hashrate => json(client.hashrate()),
+        Command::Log { tag, level } => json(client.log(&tag, &level)),
+        Command::Mempool => json(client.mempool()),
+        Command::MempoolContent { full: false } => json(client.mempool_content()),
+        Command::MempoolContent { full: true } => json(client.mempool_content_with_transactions()),
+        Command::MinerAddress => json(client.miner_address()),
+        Command::MinerThreads { threads: None } => json(client.miner_threads()),
+        Command::MinerThreads {
+            threads: Some(threads),
+        } => json(client.miner_threads_with_update(threads)),
+        Command::MinFeePerByte { fee: None } => json(client.min_fee_per_byte()),
+        Command::MinFeePerByte { fee: Some(fee) } => json(client.min_fee_per_byte_with_update(fee)),
+        Command::Mining => json(client.mining()),
+        Command::PeerCount => json(client.peer_count()),
+        Command::PeerList => json(client.peer_list()),
+        Command::PeerState { address, set: None } => json(client.peer_state(&address)),
+        Command::PeerState {
+            address,
+            set: Some(set),
+        } => json(client.peer_state_with_update(&address, &set)),
+        Command::PoolConfirmedBalance => json(client.pool_confirmed_balance()),
+        Command::PoolConnectionState => json(client.pool_connection_state()),
+        Command::SendRawTransaction { transaction } => {
+            json(client.send_raw_transaction(&transaction))
+        }
+        Command::SendTransaction {
+            from,
+            to,
+            value,
+            fee,
+        } => json(client.send_transaction(&outgoing(&from, &to, value, fee)?)),
+        Command::SubmitBlock { block } => json(client.submit_block(&block)),
+        Command::Syncing => json(client.syncing()),
+        Command::Shell => Err(CliError::Invalid("the shell is already running")),
+    }
+}
+
+fn json<T: Serialize>(result: Result<T, Error>) -> Result<Value, CliError> {
+    Ok(serde_json::to_value(result?)?)
+}
+
+fn outgoing<'a>(
+    from: &'a str,
+    to: &'a str,
+    value: u64,
+    fee: u64,
+) -> Result<OutgoingTransaction<'a>, CliError> {
+    if fee > u64::from(u32::MAX) {
+        return Err(CliError::Invalid("fee is too large"));
+    }
+    Ok(OutgoingTransaction {
+        from,
+        to,
+        value,
+        fee: fee as u32,
+    })
+}
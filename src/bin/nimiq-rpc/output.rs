@@ -0,0 +1,178 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// Number of Luna in one NIM.
+const LUNA_PER_NIM: u64 = 100_000;
+
+/// Fields that hold an amount in Luna.
+const AMOUNT_FIELDS: &[&str] = &["balance", "fee", "value"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Table,
+}
+
+/// Formats an amount in Luna as NIM, e.g. `1.50000 NIM`.
+pub fn format_nim(luna: u64) -> String {
+    format!("{}.{:05} NIM", luna / LUNA_PER_NIM, luna % LUNA_PER_NIM)
+}
+
+/// Parses an amount in NIM with up to five decimals into Luna.
+pub fn parse_nim(nim: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid NIM amount '{}'", nim);
+    let nim = nim.trim().trim_end_matches("NIM").trim();
+    let (integer, fraction) = match nim.find('.') {
+        Some(index) => (&nim[..index], &nim[index + 1..]),
+        None => (nim, ""),
+    };
+    if fraction.len() > 5 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let integer: u64 = if integer.is_empty() {
+        0
+    } else {
+        integer.parse().map_err(|_| invalid())?
+    };
+    let fraction: u64 = format!("{:0<5}", fraction).parse().map_err(|_| invalid())?;
+    integer
+        .checked_mul(LUNA_PER_NIM)
+        .and_then(|luna| luna.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+/// Replaces all amounts in Luna by their NIM representation.
+pub fn format_amounts(value: &mut Value) {
+    match value {
+        Value::Array(values) => values.iter_mut().for_each(format_amounts),
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                match field.as_u64() {
+                    Some(luna) if AMOUNT_FIELDS.contains(&key.as_str()) => {
+                        *field = Value::from(format_nim(luna))
+                    }
+                    _ => format_amounts(field),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Formats the amounts in the result of a command as NIM.
+///
+/// `is_amount` marks results that are amounts themselves, or lists of amounts.
+pub fn format_result(value: &mut Value, is_amount: bool) {
+    match value {
+        Value::Number(luna) if is_amount => {
+            if let Some(luna) = luna.as_u64() {
+                *value = Value::from(format_nim(luna));
+            }
+        }
+        Value::Array(values) if is_amount => values
+            .iter_mut()
+            .for_each(|value| format_result(value, true)),
+        value => format_amounts(value),
+    }
+}
+
+/// Prints a value to stdout in the given format.
+///
+/// Objects are shown as a two column table of fields and values, arrays of objects as one row
+/// per element.
+pub fn print(value: &Value, format: Format) {
+    match (format, value) {
+        (Format::Json, _) => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+        (Format::Table, Value::Object(fields)) => {
+            let rows: Vec<Vec<String>> = fields
+                .iter()
+                .map(|(key, field)| vec![key.clone(), cell(field)])
+                .collect();
+            print_table(&[], &rows);
+        }
+        (Format::Table, Value::Array(values)) if values.iter().all(Value::is_object) => {
+            let columns: Vec<String> = match values.first() {
+                Some(Value::Object(fields)) => fields
+                    .iter()
+                    .filter(|(_, field)| !field.is_array() && !field.is_object())
+                    .map(|(key, _)| key.clone())
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let rows: Vec<Vec<String>> = values
+                .iter()
+                .map(|value| columns.iter().map(|column| cell(&value[column])).collect())
+                .collect();
+            print_table(&columns, &rows);
+        }
+        (Format::Table, Value::Array(values)) => {
+            for value in values {
+                println!("{}", cell(value));
+            }
+        }
+        (Format::Table, value) => println!("{}", cell(value)),
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_owned(),
+        Value::String(string) => string.clone(),
+        Value::Array(values) if values.iter().any(|value| value.is_object()) => {
+            format!("[{} items]", values.len())
+        }
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join("\n"),
+        value => value.to_string(),
+    }
+}
+
+fn print_table(header: &[String], rows: &[Vec<String>]) {
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain(Some(header.len()))
+        .max()
+        .unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in Some(header)
+        .into_iter()
+        .chain(rows.iter().map(Vec::as_slice))
+    {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            let longest = cell.lines().map(|line| line.chars().count()).max();
+            *width = (*width).max(longest.unwrap_or(0));
+        }
+    }
+
+    let print_row = |row: &[String]| {
+        let lines = row
+            .iter()
+            .map(|cell| cell.lines().count())
+            .max()
+            .unwrap_or(0);
+        for line in 0..lines.max(1) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    format!(
+                        "{:width$}",
+                        cell.lines().nth(line).unwrap_or(""),
+                        width = width
+                    )
+                })
+                .collect();
+            println!("{}", cells.join("  ").trim_end());
+        }
+    };
+
+    if !header.is_empty() {
+        print_row(header);
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        println!("{}", rule.join("  "));
+    }
+    for row in rows {
+        print_row(row);
+    }
+}
@@ -0,0 +1,389 @@
+use std::env;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{CommandFactory, Parser};
+use nimiq_rpc::Client;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde_json::Value;
+
+use crate::output::{self, Format};
+use crate::{run, Cli, Command};
+
+const PROMPT: &str = "nimiq> ";
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A single command line of the shell, parsed with the same subcommands as the binary.
+#[derive(Debug, Parser)]
+#[command(no_binary_name = true, disable_version_flag = true)]
+struct Line {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// An interactive shell that keeps one client for all commands.
+///
+/// Besides every subcommand of the binary, the shell understands:
+///
+/// * `watch [-n <seconds>] <command>` to repeat a command until Enter is pressed.
+/// * `<command> | <command>` to pass the result of a command to the next one. The result
+///   replaces `$` or `$.field` in the arguments of the next command, or is appended if there is
+///   no placeholder. When the result is a list, the next command runs once per element.
+pub struct Shell {
+    client: Client,
+    format: Format,
+    luna: bool,
+}
+
+impl Shell {
+    pub fn new(client: Client, format: Format, luna: bool) -> Shell {
+        Shell {
+            client,
+            format,
+            luna,
+        }
+    }
+
+    /// Reads and executes commands until the input ends or `exit` is entered.
+    pub fn run(&self) {
+        let mut editor: Editor<ShellHelper, FileHistory> = match Editor::new() {
+            Ok(editor) => editor,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return;
+            }
+        };
+        editor.set_helper(Some(ShellHelper {
+            commands: commands(),
+            addresses: self.addresses(),
+        }));
+        let history = history_path();
+        if let Some(history) = &history {
+            let _ = editor.load_history(history);
+        }
+
+        loop {
+            let line = match editor.readline(PROMPT) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    break;
+                }
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let _ = editor.add_history_entry(line);
+            if line == "exit" || line == "quit" {
+                break;
+            }
+
+            if let Err(err) = self.execute(line) {
+                eprintln!("{}", err);
+            }
+            if line.contains("create-account") || line.contains("create_account") {
+                if let Some(helper) = editor.helper_mut() {
+                    helper.addresses = self.addresses();
+                }
+            }
+        }
+
+        if let Some(history) = &history {
+            let _ = editor.save_history(history);
+        }
+    }
+
+    fn execute(&self, line: &str) -> Result<(), String> {
+        let mut pipeline = split(line)?;
+        let watch = pipeline[0].first().map(String::as_str) == Some("watch");
+        if !watch {
+            return self.print(&pipeline);
+        }
+
+        pipeline[0].remove(0);
+        let mut interval = WATCH_INTERVAL;
+        if pipeline[0].first().map(String::as_str) == Some("-n") {
+            let seconds: f64 = pipeline[0]
+                .get(1)
+                .and_then(|seconds| seconds.parse().ok())
+                .filter(|seconds| *seconds > 0.0)
+                .ok_or("error: watch -n expects a positive number of seconds")?;
+            interval = Duration::from_secs_f64(seconds);
+            pipeline[0].drain(..2);
+        }
+        if pipeline[0].is_empty() {
+            return Err("error: watch expects a command".to_owned());
+        }
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&stopped);
+        thread::spawn(move || {
+            let _ = io::stdin().lock().read_line(&mut String::new());
+            stop.store(true, Ordering::SeqCst);
+        });
+
+        let command = line["watch".len()..].trim();
+        while !stopped.load(Ordering::SeqCst) {
+            println!("Every {:?}: {} (press Enter to stop)", interval, command);
+            if let Err(err) = self.print(&pipeline) {
+                eprintln!("{}", err);
+            }
+            let mut waited = Duration::from_millis(0);
+            while waited < interval && !stopped.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(50));
+                waited += Duration::from_millis(50);
+            }
+        }
+        Ok(())
+    }
+
+    fn print(&self, pipeline: &[Vec<String>]) -> Result<(), String> {
+        let (mut value, is_amount) = self.pipe(pipeline)?;
+        if !self.luna {
+            output::format_result(&mut value, is_amount);
+        }
+        output::print(&value, self.format);
+        Ok(())
+    }
+
+    /// Runs the commands of a pipeline and returns the result of the last one.
+    fn pipe(&self, pipeline: &[Vec<String>]) -> Result<(Value, bool), String> {
+        let mut result: Option<Value> = None;
+        let mut is_amount = false;
+
+        for words in pipeline {
+            let fan_out = matches!(result, Some(Value::Array(_)));
+            let invocations = match result.take() {
+                None => vec![words.clone()],
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .map(|value| bind(words, value))
+                    .collect::<Result<_, _>>()?,
+                Some(value) => vec![bind(words, &value)?],
+            };
+
+            let mut values = Vec::new();
+            for words in invocations {
+                let command = parse(&words)?;
+                is_amount = command.is_amount();
+                if let Command::Shell = command {
+                    return Err("error: the shell is already running".to_owned());
+                }
+                let value = run(&self.client, command).map_err(|err| format!("error: {}", err))?;
+                values.push(value);
+            }
+            result = Some(if fan_out {
+                Value::Array(values)
+            } else {
+                values.remove(0)
+            });
+        }
+
+        Ok((result.unwrap_or(Value::Null), is_amount))
+    }
+
+    fn addresses(&self) -> Vec<String> {
+        self.client
+            .accounts()
+            .map(|accounts| {
+                accounts
+                    .into_iter()
+                    .map(|account| account.address)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("NIMIQ_RPC_HISTORY")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".nimiq_rpc_history")))
+}
+
+fn commands() -> Vec<String> {
+    let mut commands: Vec<String> = Cli::command()
+        .get_subcommands()
+        .map(|command| command.get_name().to_owned())
+        .filter(|name| name != "shell")
+        .collect();
+    commands.extend(
+        ["watch", "help", "exit"]
+            .iter()
+            .map(|name| name.to_string()),
+    );
+    commands.sort();
+    commands
+}
+
+fn parse(words: &[String]) -> Result<Command, String> {
+    let mut words = words.to_vec();
+    if let Some(name) = words.first_mut() {
+        *name = name.replace('_', "-");
+    }
+    Line::try_parse_from(words)
+        .map(|line| line.command)
+        .map_err(|err| err.render().to_string().trim_end().to_owned())
+}
+
+/// Substitutes the result of the previous command into the words of the next one.
+fn bind(words: &[String], value: &Value) -> Result<Vec<String>, String> {
+    let mut bound = false;
+    let mut words: Vec<String> = words
+        .iter()
+        .map(|word| {
+            if word == "$" || word.starts_with("$.") {
+                bound = true;
+                let field = word[1..]
+                    .split('.')
+                    .filter(|key| !key.is_empty())
+                    .try_fold(value, |value, key| value.get(key));
+                field
+                    .map(argument)
+                    .ok_or_else(|| format!("error: no field {} in {}", word, value))?
+            } else {
+                Ok(word.clone())
+            }
+        })
+        .collect::<Result<_, String>>()?;
+    if !bound {
+        words.push(argument(value)?);
+    }
+    Ok(words)
+}
+
+fn argument(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(string) => Ok(string.clone()),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        _ => Err(format!(
+            "error: cannot pass {} as an argument, select a field with $.field",
+            value
+        )),
+    }
+}
+
+/// Splits a command line into the words of its pipeline stages.
+///
+/// Words are separated by whitespace unless they are enclosed in single or double quotes.
+fn split(line: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut pipeline = vec![Vec::new()];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, '|') => {
+                pipeline.last_mut().unwrap().extend(word.take());
+                pipeline.push(Vec::new());
+            }
+            (None, c) if c.is_whitespace() => pipeline.last_mut().unwrap().extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("error: unterminated quote".to_owned());
+    }
+    pipeline.last_mut().unwrap().extend(word.take());
+
+    if pipeline.iter().any(Vec::is_empty) {
+        return Err("error: empty command in pipeline".to_owned());
+    }
+    Ok(pipeline)
+}
+
+struct ShellHelper {
+    commands: Vec<String>,
+    addresses: Vec<String>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+
+        // Find where the word under the cursor starts, an open quote belongs to the word.
+        let mut start = 0;
+        let mut quote = None;
+        for (index, c) in line.char_indices() {
+            match quote {
+                Some(open) if c == open => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    start = index;
+                }
+                None if c.is_whitespace() || c == '|' => start = index + c.len_utf8(),
+                None => {}
+            }
+        }
+        let prefix = line[start..].trim_start_matches(['"', '\'']);
+
+        let stage = &line[line[..start].rfind('|').map_or(0, |index| index + 1)..start];
+        let mut words = stage.split_whitespace().peekable();
+        if words.peek() == Some(&"watch") {
+            words.next();
+            if words.peek() == Some(&"-n") {
+                words.next();
+                words.next();
+            }
+        }
+
+        let candidates: Vec<Pair> = if words.next().is_none() {
+            self.commands
+                .iter()
+                .filter(|command| command.starts_with(&prefix.replace('_', "-")))
+                .map(|command| Pair {
+                    display: command.clone(),
+                    replacement: format!("{} ", command),
+                })
+                .collect()
+        } else {
+            let prefix = prefix.to_uppercase();
+            self.addresses
+                .iter()
+                .filter(|address| address.starts_with(&prefix))
+                .map(|address| Pair {
+                    display: address.clone(),
+                    replacement: format!("\"{}\" ", address),
+                })
+                .collect()
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
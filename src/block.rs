@@ -0,0 +1,621 @@
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+use crate::merkle;
+use crate::primitives::{Body, FullBlock};
+use crate::superblock;
+
+/// Size of a hash in a serialized block in bytes.
+pub const HASH_SIZE: usize = 32;
+
+/// Size of an address in a serialized block in bytes.
+pub const ADDRESS_SIZE: usize = 20;
+
+/// Hash of the mainnet genesis block, part of every interlink hash.
+pub const MAINNET_GENESIS_HASH: [u8; HASH_SIZE] = [
+    0x26, 0x4a, 0xaf, 0x8a, 0x4f, 0x98, 0x28, 0xa7, 0x6c, 0x55, 0x06, 0x35, 0xda, 0x07, 0x8e, 0xb4,
+    0x66, 0x30, 0x6a, 0x18, 0x9f, 0xcc, 0x03, 0x71, 0x0b, 0xee, 0x9f, 0x64, 0x9c, 0x86, 0x9d, 0x12,
+];
+
+/// Error while decoding a serialized block or one of its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input is not valid hex.
+    InvalidHex,
+    /// The input ended before the structure was complete.
+    UnexpectedEnd,
+    /// A field has a value that cannot be encoded, e.g. a hash of the wrong size.
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex => write!(f, "invalid hex"),
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::InvalidField(field) => write!(f, "invalid {}", field),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// A block header in its binary form, as hashed for the proof of work.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::block::BlockHeader;
+/// let header = BlockHeader {
+///     height: 882419,
+///     n_bits: 0x1f010000,
+///     ..BlockHeader::default()
+/// };
+/// let bytes = header.serialize();
+/// assert_eq!(bytes.len(), BlockHeader::SIZE);
+/// assert_eq!(BlockHeader::deserialize(&bytes).unwrap(), header);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: u16,
+    pub prev_hash: [u8; HASH_SIZE],
+    pub interlink_hash: [u8; HASH_SIZE],
+    pub body_hash: [u8; HASH_SIZE],
+    pub accounts_hash: [u8; HASH_SIZE],
+    pub n_bits: u32,
+    pub height: u32,
+    pub timestamp: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    /// Size of a serialized header in bytes.
+    pub const SIZE: usize = 2 + 4 * HASH_SIZE + 4 * 4;
+
+    /// Builds the header of a block template, with the given timestamp and nonce.
+    pub fn from_template(
+        template: &FullBlock,
+        timestamp: u32,
+        nonce: u32,
+    ) -> Result<BlockHeader, DecodeError> {
+        let header = &template.header;
+        Ok(BlockHeader {
+            version: field(header.version, "version")?,
+            prev_hash: hash(&header.prev_hash, "prevHash")?,
+            interlink_hash: hash(&header.interlink_hash, "interlinkHash")?,
+            body_hash: hash(&template.body.hash, "bodyHash")?,
+            accounts_hash: hash(&header.accounts_hash, "accountsHash")?,
+            n_bits: field(header.n_bits, "nBits")?,
+            height: field(header.height, "height")?,
+            timestamp,
+            nonce,
+        })
+    }
+
+    /// Serializes the header in network byte order.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BlockHeader::SIZE);
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.prev_hash);
+        bytes.extend_from_slice(&self.interlink_hash);
+        bytes.extend_from_slice(&self.body_hash);
+        bytes.extend_from_slice(&self.accounts_hash);
+        bytes.extend_from_slice(&self.n_bits.to_be_bytes());
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+        bytes
+    }
+
+    /// Deserializes a header from the start of `bytes`.
+    pub fn deserialize(bytes: &[u8]) -> Result<BlockHeader, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        BlockHeader::read(&mut reader)
+    }
+
+    /// Returns the Blake2b hash of the header, which is the hash of the block.
+    pub fn hash(&self) -> [u8; HASH_SIZE] {
+        merkle::hash(&self.serialize())
+    }
+
+    /// Deserializes a hex encoded header.
+    pub fn from_hex(header: &str) -> Result<BlockHeader, DecodeError> {
+        BlockHeader::deserialize(&hex::decode(header).map_err(|_| DecodeError::InvalidHex)?)
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<BlockHeader, DecodeError> {
+        Ok(BlockHeader {
+            version: reader.u16()?,
+            prev_hash: reader.hash()?,
+            interlink_hash: reader.hash()?,
+            body_hash: reader.hash()?,
+            accounts_hash: reader.hash()?,
+            n_bits: reader.u32()?,
+            height: reader.u32()?,
+            timestamp: reader.u32()?,
+            nonce: reader.u32()?,
+        })
+    }
+}
+
+/// The interlink of a block, the hashes of the latest blocks at each superblock level.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockInterlink {
+    pub hashes: Vec<[u8; HASH_SIZE]>,
+}
+
+impl BlockInterlink {
+    /// Serializes the interlink, leaving out hashes that repeat the previous one.
+    ///
+    /// The first hash is compared with `prev_hash`, the hash of the block before the block the
+    /// interlink belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than 255 hashes.
+    pub fn serialize(&self, prev_hash: &[u8; HASH_SIZE]) -> Vec<u8> {
+        let count = u8::try_from(self.hashes.len()).expect("too many interlink hashes");
+        let (repeat_bits, compressed) = self.compress(prev_hash);
+        let mut bytes = vec![count];
+        bytes.extend_from_slice(&repeat_bits);
+        for hash in compressed {
+            bytes.extend_from_slice(hash);
+        }
+        bytes
+    }
+
+    /// Computes the interlink hash found in the header of the block the interlink belongs to.
+    ///
+    /// The hash is the merkle root over the hash of the repeat bits, the genesis hash of the
+    /// network and the compressed hashes, which are already hashes and used as they are.
+    pub fn hash(
+        &self,
+        prev_hash: &[u8; HASH_SIZE],
+        genesis_hash: &[u8; HASH_SIZE],
+    ) -> [u8; HASH_SIZE] {
+        let (repeat_bits, compressed) = self.compress(prev_hash);
+        let mut leaves = vec![merkle::hash(&repeat_bits), *genesis_hash];
+        leaves.extend(compressed.into_iter().copied());
+        merkle::root(&leaves)
+    }
+
+    /// Computes the interlink of the successor of a block.
+    ///
+    /// `hash`, `pow` and `target` belong to the block this interlink is part of, `next_target`
+    /// is the target of its successor. The block takes the positions of all levels it reaches
+    /// relative to the next target, and the remaining positions are shifted by the change in
+    /// target.
+    pub fn next(
+        &self,
+        hash: &[u8; HASH_SIZE],
+        pow: &[u8; HASH_SIZE],
+        target: &[u8; HASH_SIZE],
+        next_target: &[u8; HASH_SIZE],
+    ) -> BlockInterlink {
+        let next_target_depth = superblock::target_depth(next_target);
+        let occurrences = (superblock::hash_depth(pow) - next_target_depth + 1).max(0);
+        let offset = occurrences + next_target_depth - superblock::target_depth(target);
+
+        let mut hashes = vec![*hash; occurrences as usize];
+        hashes.extend(self.hashes.iter().skip(offset.max(0) as usize));
+        BlockInterlink { hashes }
+    }
+
+    /// Splits the interlink into its repeat bits and the hashes that differ from their
+    /// predecessor.
+    fn compress<'a>(
+        &'a self,
+        prev_hash: &'a [u8; HASH_SIZE],
+    ) -> (Vec<u8>, Vec<&'a [u8; HASH_SIZE]>) {
+        let mut repeat_bits = vec![0u8; self.hashes.len().div_ceil(8)];
+        let mut compressed = Vec::new();
+        let mut last = prev_hash;
+        for (i, hash) in self.hashes.iter().enumerate() {
+            if hash == last {
+                repeat_bits[i / 8] |= 0x80 >> (i % 8);
+            } else {
+                compressed.push(hash);
+                last = hash;
+            }
+        }
+        (repeat_bits, compressed)
+    }
+
+    /// Deserializes an interlink from the start of `bytes`.
+    pub fn deserialize(
+        bytes: &[u8],
+        prev_hash: &[u8; HASH_SIZE],
+    ) -> Result<BlockInterlink, DecodeError> {
+        BlockInterlink::read(&mut Reader::new(bytes), prev_hash)
+    }
+
+    /// Deserializes a hex encoded interlink, as found in a block template.
+    pub fn from_hex(
+        interlink: &str,
+        prev_hash: &[u8; HASH_SIZE],
+    ) -> Result<BlockInterlink, DecodeError> {
+        let bytes = hex::decode(interlink).map_err(|_| DecodeError::InvalidHex)?;
+        BlockInterlink::deserialize(&bytes, prev_hash)
+    }
+
+    pub(crate) fn read(
+        reader: &mut Reader,
+        prev_hash: &[u8; HASH_SIZE],
+    ) -> Result<BlockInterlink, DecodeError> {
+        let count = usize::from(reader.u8()?);
+        let repeat_bits = reader.take(count.div_ceil(8))?;
+        let mut hashes = Vec::with_capacity(count);
+        let mut last = *prev_hash;
+        for i in 0..count {
+            if repeat_bits[i / 8] & (0x80 >> (i % 8)) == 0 {
+                last = reader.hash()?;
+            }
+            hashes.push(last);
+        }
+        Ok(BlockInterlink { hashes })
+    }
+}
+
+/// A block body in its binary form.
+///
+/// Transactions and pruned accounts are kept serialized, they are only decoded as far as needed
+/// to find their length and hash.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockBody {
+    pub miner_addr: [u8; ADDRESS_SIZE],
+    /// At most 255 bytes.
+    pub extra_data: Vec<u8>,
+    pub transactions: Vec<Vec<u8>>,
+    pub pruned_accounts: Vec<Vec<u8>>,
+}
+
+impl BlockBody {
+    /// Decodes the body of a block template.
+    pub fn from_template(body: &Body) -> Result<BlockBody, DecodeError> {
+        let miner_addr = hex::decode(&body.miner_addr)
+            .ok()
+            .and_then(|addr| addr.try_into().ok())
+            .ok_or(DecodeError::InvalidField("minerAddr"))?;
+        let extra_data =
+            hex::decode(&body.extra_data).map_err(|_| DecodeError::InvalidField("extraData"))?;
+        if extra_data.len() > usize::from(u8::MAX) {
+            return Err(DecodeError::InvalidField("extraData"));
+        }
+        let transactions = decode_all(&body.transactions, "transactions", |reader| {
+            read_transaction(reader).map(|_| ())
+        })?;
+        let pruned_accounts =
+            decode_all(&body.pruned_accounts, "prunedAccounts", read_pruned_account)?;
+
+        Ok(BlockBody {
+            miner_addr,
+            extra_data,
+            transactions,
+            pruned_accounts,
+        })
+    }
+
+    /// Serializes the body in network byte order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extra data is longer than 255 bytes or there are more than 65535
+    /// transactions or pruned accounts.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = self.miner_addr.to_vec();
+        bytes.push(u8::try_from(self.extra_data.len()).expect("extra data too long"));
+        bytes.extend_from_slice(&self.extra_data);
+        for items in [&self.transactions, &self.pruned_accounts] {
+            let count = u16::try_from(items.len()).expect("too many body entries");
+            bytes.extend_from_slice(&count.to_be_bytes());
+            for item in items {
+                bytes.extend_from_slice(item);
+            }
+        }
+        bytes
+    }
+
+    /// Deserializes a body from the start of `bytes`.
+    pub fn deserialize(bytes: &[u8]) -> Result<BlockBody, DecodeError> {
+        BlockBody::read(&mut Reader::new(bytes))
+    }
+
+    /// Computes the body hash, the merkle root over the miner address, the extra data, the
+    /// transaction hashes and the pruned accounts.
+    pub fn hash(&self) -> Result<[u8; HASH_SIZE], DecodeError> {
+        let mut transaction_hashes = Vec::with_capacity(self.transactions.len());
+        for transaction in &self.transactions {
+            let mut reader = Reader::new(transaction);
+            let (_, hash) = read_transaction(&mut reader)?;
+            if !reader.bytes.is_empty() {
+                return Err(DecodeError::InvalidField("transactions"));
+            }
+            transaction_hashes.push(hash);
+        }
+        Ok(merkle::body_hash(
+            &self.miner_addr,
+            &self.extra_data,
+            &transaction_hashes,
+            &self.pruned_accounts,
+        ))
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<BlockBody, DecodeError> {
+        let mut miner_addr = [0; ADDRESS_SIZE];
+        miner_addr.copy_from_slice(reader.take(ADDRESS_SIZE)?);
+        let extra_data_size = usize::from(reader.u8()?);
+        let extra_data = reader.take(extra_data_size)?.to_vec();
+
+        let mut transactions = Vec::new();
+        for _ in 0..reader.u16()? {
+            transactions.push(read_transaction(reader)?.0.to_vec());
+        }
+        let mut pruned_accounts = Vec::new();
+        for _ in 0..reader.u16()? {
+            let start = reader.bytes;
+            read_pruned_account(reader)?;
+            pruned_accounts.push(start[..start.len() - reader.bytes.len()].to_vec());
+        }
+
+        Ok(BlockBody {
+            miner_addr,
+            extra_data,
+            transactions,
+            pruned_accounts,
+        })
+    }
+}
+
+/// A complete block in its binary form, as accepted by `submitBlock`.
+///
+/// # Example
+///
+/// Mining a template with new extra data:
+///
+/// ```no_run
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::block::RawBlock;
+/// let client = Client::new("http://seed-host.com:8648");
+/// let template = client.get_block_template().unwrap();
+/// let mut block = RawBlock::from_template(&template, 1_600_000_000, 0).unwrap();
+/// block.set_extra_data(b"my pool".to_vec()).unwrap();
+/// block.header.nonce = 42;
+/// client.submit_block(&block.to_hex()).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawBlock {
+    pub header: BlockHeader,
+    pub interlink: BlockInterlink,
+    /// Left out for light blocks.
+    pub body: Option<BlockBody>,
+}
+
+impl RawBlock {
+    /// Builds a block from a template, with the given timestamp and nonce.
+    pub fn from_template(
+        template: &FullBlock,
+        timestamp: u32,
+        nonce: u32,
+    ) -> Result<RawBlock, DecodeError> {
+        let header = BlockHeader::from_template(template, timestamp, nonce)?;
+        let interlink = BlockInterlink::from_hex(&template.interlink, &header.prev_hash)?;
+        let body = BlockBody::from_template(&template.body)?;
+        Ok(RawBlock {
+            header,
+            interlink,
+            body: Some(body),
+        })
+    }
+
+    /// Returns the hash of the block.
+    pub fn hash(&self) -> [u8; HASH_SIZE] {
+        self.header.hash()
+    }
+
+    /// Returns whether the interlink hashes to the interlink hash of the header.
+    pub fn verify_interlink(&self, genesis_hash: &[u8; HASH_SIZE]) -> bool {
+        self.interlink.hash(&self.header.prev_hash, genesis_hash) == self.header.interlink_hash
+    }
+
+    /// Replaces the extra data of the body and updates the body hash of the header.
+    ///
+    /// Fails if the block has no body, the extra data is longer than 255 bytes or a transaction
+    /// of the body cannot be decoded.
+    pub fn set_extra_data(&mut self, extra_data: Vec<u8>) -> Result<(), DecodeError> {
+        if extra_data.len() > usize::from(u8::MAX) {
+            return Err(DecodeError::InvalidField("extraData"));
+        }
+        let body = self
+            .body
+            .as_mut()
+            .ok_or(DecodeError::InvalidField("body"))?;
+        body.extra_data = extra_data;
+        self.header.body_hash = body.hash()?;
+        Ok(())
+    }
+
+    /// Serializes the block in network byte order.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = self.header.serialize();
+        bytes.extend(self.interlink.serialize(&self.header.prev_hash));
+        match &self.body {
+            Some(body) => {
+                bytes.push(1);
+                bytes.extend(body.serialize());
+            }
+            None => bytes.push(0),
+        }
+        bytes
+    }
+
+    /// Returns the hex encoded block, ready for `submitBlock`.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.serialize())
+    }
+
+    /// Deserializes a block from the start of `bytes`.
+    pub fn deserialize(bytes: &[u8]) -> Result<RawBlock, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let header = BlockHeader::read(&mut reader)?;
+        let interlink = BlockInterlink::read(&mut reader, &header.prev_hash)?;
+        let body = match reader.u8()? {
+            0 => None,
+            1 => Some(BlockBody::read(&mut reader)?),
+            _ => return Err(DecodeError::InvalidField("body")),
+        };
+        Ok(RawBlock {
+            header,
+            interlink,
+            body,
+        })
+    }
+
+    /// Deserializes a hex encoded block.
+    pub fn from_hex(block: &str) -> Result<RawBlock, DecodeError> {
+        RawBlock::deserialize(&hex::decode(block).map_err(|_| DecodeError::InvalidHex)?)
+    }
+}
+
+/// Reads big-endian values from a byte slice.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, DecodeError> {
+        let mut value = [0; 2];
+        value.copy_from_slice(self.take(2)?);
+        Ok(u16::from_be_bytes(value))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DecodeError> {
+        let mut value = [0; 4];
+        value.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(value))
+    }
+
+    pub(crate) fn hash(&mut self) -> Result<[u8; HASH_SIZE], DecodeError> {
+        let mut hash = [0; HASH_SIZE];
+        hash.copy_from_slice(self.take(HASH_SIZE)?);
+        Ok(hash)
+    }
+}
+
+/// Reads a serialized transaction and returns its bytes and its hash.
+///
+/// The hash covers the content of the transaction without its signature or proof.
+fn read_transaction<'a>(
+    reader: &mut Reader<'a>,
+) -> Result<(&'a [u8], [u8; HASH_SIZE]), DecodeError> {
+    let start = reader.bytes;
+    let content = match reader.u8()? {
+        // Basic: sender public key, recipient, value, fee, validity start height, network id,
+        // signature.
+        0 => {
+            let public_key = reader.take(32)?;
+            let rest = reader.take(ADDRESS_SIZE + 8 + 8 + 4 + 1)?;
+            reader.take(64)?;
+
+            let mut content = vec![0, 0];
+            content.extend_from_slice(&merkle::hash(public_key)[..ADDRESS_SIZE]);
+            content.push(0);
+            content.extend_from_slice(&rest[..ADDRESS_SIZE]);
+            content.push(0);
+            content.extend_from_slice(&rest[ADDRESS_SIZE..]);
+            content.push(0);
+            content
+        }
+        // Extended: the content is serialized as is, followed by the proof.
+        1 => {
+            let content_start = reader.bytes;
+            let data_size = usize::from(reader.u16()?);
+            reader.take(data_size + 2 * (ADDRESS_SIZE + 1) + 8 + 8 + 4 + 1 + 1)?;
+            let content = content_start[..content_start.len() - reader.bytes.len()].to_vec();
+            let proof_size = usize::from(reader.u16()?);
+            reader.take(proof_size)?;
+            content
+        }
+        _ => return Err(DecodeError::InvalidField("transaction type")),
+    };
+    let bytes = &start[..start.len() - reader.bytes.len()];
+    Ok((bytes, merkle::hash(&content)))
+}
+
+/// Reads a serialized pruned account, an address followed by the account.
+fn read_pruned_account(reader: &mut Reader) -> Result<(), DecodeError> {
+    reader.take(ADDRESS_SIZE)?;
+    let account_type = reader.u8()?;
+    // Balance.
+    reader.take(8)?;
+    match account_type {
+        0 => {}
+        // Vesting: owner, start, step blocks, step amount, total amount.
+        1 => {
+            reader.take(ADDRESS_SIZE + 4 + 4 + 8 + 8)?;
+        }
+        // HTLC: sender, recipient, hash algorithm, hash root, hash count, timeout, total amount.
+        2 => {
+            reader.take(2 * ADDRESS_SIZE)?;
+            let root_size = match reader.u8()? {
+                1..=3 => 32,
+                4 => 64,
+                _ => return Err(DecodeError::InvalidField("hash algorithm")),
+            };
+            reader.take(root_size + 1 + 4 + 8)?;
+        }
+        _ => return Err(DecodeError::InvalidField("account type")),
+    }
+    Ok(())
+}
+
+/// Decodes a list of hex encoded items, checking that each is exactly one well formed item.
+fn decode_all(
+    items: &[String],
+    name: &'static str,
+    read: impl Fn(&mut Reader) -> Result<(), DecodeError>,
+) -> Result<Vec<Vec<u8>>, DecodeError> {
+    items
+        .iter()
+        .map(|item| {
+            let bytes = hex::decode(item).map_err(|_| DecodeError::InvalidField(name))?;
+            let mut reader = Reader::new(&bytes);
+            read(&mut reader).map_err(|_| DecodeError::InvalidField(name))?;
+            if !reader.bytes.is_empty() {
+                return Err(DecodeError::InvalidField(name));
+            }
+            Ok(bytes)
+        })
+        .collect()
+}
+
+/// Decodes a hex encoded hash.
+pub(crate) fn hash(hash: &str, name: &'static str) -> Result<[u8; HASH_SIZE], DecodeError> {
+    let bytes = hex::decode(hash).map_err(|_| DecodeError::InvalidField(name))?;
+    if bytes.len() != HASH_SIZE {
+        return Err(DecodeError::InvalidField(name));
+    }
+    let mut hash = [0; HASH_SIZE];
+    hash.copy_from_slice(&bytes);
+    Ok(hash)
+}
+
+fn field<T: TryFrom<u64>>(value: u64, name: &'static str) -> Result<T, DecodeError> {
+    T::try_from(value).map_err(|_| DecodeError::InvalidField(name))
+}
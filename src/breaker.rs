@@ -0,0 +1,239 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+
+use crate::transport::{self, Transport};
+
+/// The state of a `CircuitBreaker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent to the node.
+    Closed,
+    /// The node failed too often, requests fail without being sent.
+    Open,
+    /// The cooldown is over and a probe call checks whether the node is back.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// The error of a request that was not sent because the circuit is open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitOpen {
+    /// Consecutive failures that opened the circuit.
+    pub failures: u32,
+    /// Time until the node is probed again, zero while a probe is running.
+    pub retry_in: Duration,
+}
+
+impl CircuitOpen {
+    /// Returns the `CircuitOpen` wrapped in `err`, if it is one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::breaker::CircuitOpen;
+    /// # let client = nimiq_rpc::Client::new("http://seed-host.com:8648");
+    /// if let Err(err) = client.block_number() {
+    ///     if let Some(open) = CircuitOpen::from_error(&err) {
+    ///         println!("node is down, retrying in {:?}", open.retry_in);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_error(err: &Error) -> Option<&CircuitOpen> {
+        transport::downcast_error(err)
+    }
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "circuit open after {} consecutive failures, retrying in {:?}",
+            self.failures, self.retry_in
+        )
+    }
+}
+
+impl error::Error for CircuitOpen {}
+
+impl From<CircuitOpen> for Error {
+    fn from(open: CircuitOpen) -> Error {
+        transport::typed_error(io::ErrorKind::ConnectionRefused, open)
+    }
+}
+
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    opened: Instant,
+}
+
+/// Forwards requests to another transport until it fails too often, then fails fast.
+///
+/// The circuit opens after `threshold` consecutive requests failed without an answer from the
+/// node, and requests fail with a [`CircuitOpen`] error while it is open. After the cooldown
+/// the next request first sends a probe call, `consensus` by default, and the circuit closes
+/// again if the node answers it. Error responses of the node do not count as failures.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::breaker::CircuitBreaker;
+/// let agent = jsonrpc::client::Client::new("http://seed-host.com:8648".to_owned(), None, None);
+/// let breaker = Arc::new(CircuitBreaker::new(agent).threshold(3));
+/// let client = Client::new_with_transport(Arc::clone(&breaker));
+/// let result = client.block_number();
+/// println!("circuit {}", breaker.state());
+/// ```
+pub struct CircuitBreaker<T> {
+    inner: T,
+    threshold: u32,
+    cooldown: Duration,
+    probe: String,
+    circuit: Mutex<Circuit>,
+}
+
+impl<T: Transport> CircuitBreaker<T> {
+    /// Guards `inner`, opening after 5 consecutive failures for a cooldown of 30 seconds.
+    pub fn new(inner: T) -> CircuitBreaker<T> {
+        CircuitBreaker {
+            inner,
+            threshold: 5,
+            cooldown: Duration::from_secs(30),
+            probe: "consensus".to_owned(),
+            circuit: Mutex::new(Circuit {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened: Instant::now(),
+            }),
+        }
+    }
+
+    /// Sets the number of consecutive failures that open the circuit.
+    pub fn threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    /// Sets how long the circuit stays open before the node is probed.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Sets the method called without parameters to probe the node, e.g.
+    /// `isConsensusEstablished` for Albatross nodes.
+    pub fn probe(mut self, method: &str) -> Self {
+        self.probe = method.to_owned();
+        self
+    }
+
+    /// Returns the current state, an open circuit whose cooldown is over counts as half-open.
+    pub fn state(&self) -> CircuitState {
+        let circuit = self.lock();
+        match circuit.state {
+            CircuitState::Open if circuit.opened.elapsed() >= self.cooldown => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// Returns the number of consecutive failures.
+    pub fn failures(&self) -> u32 {
+        self.lock().failures
+    }
+
+    /// Closes the circuit and forgets the failures.
+    pub fn reset(&self) {
+        let mut circuit = self.lock();
+        circuit.state = CircuitState::Closed;
+        circuit.failures = 0;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Circuit> {
+        self.circuit.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Records the outcome of a request sent to the node.
+    fn record(&self, success: bool) {
+        let mut circuit = self.lock();
+        if success {
+            circuit.state = CircuitState::Closed;
+            circuit.failures = 0;
+            return;
+        }
+        circuit.failures = circuit.failures.saturating_add(1);
+        if circuit.state == CircuitState::HalfOpen || circuit.failures >= self.threshold {
+            circuit.state = CircuitState::Open;
+            circuit.opened = Instant::now();
+        }
+    }
+
+    /// Sends the probe call, its result is only used to decide whether to close the circuit.
+    fn send_probe(&self, request: &Request) -> Result<(), Error> {
+        let probe = Request {
+            method: &self.probe,
+            params: &[],
+            id: request.id.clone(),
+            jsonrpc: request.jsonrpc,
+        };
+        let result = self.inner.send_request(&probe);
+        self.record(result.is_ok());
+        result.map(|_| ())
+    }
+}
+
+impl<T: Transport> Transport for CircuitBreaker<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let probe = {
+            let mut circuit = self.lock();
+            match circuit.state {
+                CircuitState::Closed => false,
+                CircuitState::Open if self.cooldown <= circuit.opened.elapsed() => {
+                    // Only the first request after the cooldown probes, the others keep
+                    // failing fast until it is done.
+                    circuit.state = CircuitState::HalfOpen;
+                    true
+                }
+                CircuitState::Open => {
+                    return Err(CircuitOpen {
+                        failures: circuit.failures,
+                        retry_in: self.cooldown.saturating_sub(circuit.opened.elapsed()),
+                    }
+                    .into())
+                }
+                CircuitState::HalfOpen => {
+                    return Err(CircuitOpen {
+                        failures: circuit.failures,
+                        retry_in: Duration::from_secs(0),
+                    }
+                    .into())
+                }
+            }
+        };
+        if probe {
+            self.send_probe(request)?;
+        }
+
+        let result = self.inner.send_request(request);
+        self.record(result.is_ok());
+        result
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transport::Transport;
+
+/// Confirmations after which a block is assumed to stay on the main chain, unless changed with
+/// `ResponseCache::confirmations`.
+pub const DEFAULT_CONFIRMATIONS: u64 = 10;
+
+/// How a method can be cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lookup {
+    /// Looks up a block by hash, the answer never changes once the node knows the block.
+    Hash,
+    /// Looks up a block by the number in its first parameter, which can be replaced until it is
+    /// deep enough.
+    Number,
+    /// Looks up a transaction, which can be cached once it has enough confirmations.
+    Transaction,
+}
+
+fn lookup(method: &str) -> Option<Lookup> {
+    match method {
+        "getBlockByHash" | "getBlockTransactionCountByHash" => Some(Lookup::Hash),
+        "getBlockByNumber" | "getBlockTransactionCountByNumber" => Some(Lookup::Number),
+        "getTransactionByHash"
+        | "getTransactionByBlockHashAndIndex"
+        | "getTransactionByBlockNumberAndIndex"
+        | "getTransactionReceipt" => Some(Lookup::Transaction),
+        _ => None,
+    }
+}
+
+/// Returns the transactions of a block requested with its transactions, which carry their
+/// confirmations, or nothing for blocks with transaction hashes only.
+fn block_transactions(block: &mut Value) -> impl Iterator<Item = &mut Value> {
+    block["transactions"]
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter(|transaction| transaction.is_object())
+}
+
+/// A cached result as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    params: Vec<Value>,
+    result: Value,
+}
+
+fn key(method: &str, params: &[Value]) -> String {
+    serde_json::to_string(&(method, params)).unwrap_or_default()
+}
+
+/// Results ordered by their last use.
+struct Lru {
+    capacity: usize,
+    entries: HashMap<String, (u64, Entry)>,
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<Value> {
+        let (used, entry) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.order.insert(self.tick, key.to_owned());
+        Some(entry.result.clone())
+    }
+
+    fn insert(&mut self, entry: Entry) {
+        let key = key(&entry.method, &entry.params);
+        self.tick += 1;
+        if let Some((used, _)) = self.entries.insert(key.clone(), (self.tick, entry)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            match self.order.pop_first() {
+                Some((_, oldest)) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Forwards requests to another transport and keeps the results of immutable lookups in an LRU
+/// cache.
+///
+/// Blocks by hash are cached as soon as the node knows them. Blocks by number are cached once
+/// they are `confirmations` blocks below the head, and transactions once they have that many
+/// confirmations, so results that a fork could still replace are not kept. The `confirmations`
+/// of a cached transaction, and of the transactions in a cached block, are updated to the
+/// latest known head on every hit. All other methods, such as `blockNumber` or `getBalance`,
+/// always reach the node.
+///
+/// The head is learned from `blockNumber` responses and transaction confirmations, and fetched
+/// with `blockNumber` when a block number lookup is too close to the last known head.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::cache::ResponseCache;
+/// let agent = jsonrpc::client::Client::new("http://seed-host.com:8648".to_owned(), None, None);
+/// let client = Client::new_with_transport(ResponseCache::new(agent, 10_000));
+/// let result = client.get_block_by_number(882418, true);
+/// ```
+pub struct ResponseCache<T> {
+    inner: T,
+    confirmations: u64,
+    path: Option<PathBuf>,
+    lru: Mutex<Lru>,
+    head: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T: Transport> ResponseCache<T> {
+    /// Caches up to `capacity` results of `inner`.
+    pub fn new(inner: T, capacity: usize) -> ResponseCache<T> {
+        ResponseCache {
+            inner,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            path: None,
+            lru: Mutex::new(Lru {
+                capacity,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            }),
+            head: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the confirmations a block or transaction needs before it is cached.
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations.max(1);
+        self
+    }
+
+    /// Loads the cache from the file at `path` if it exists, and saves it there when the
+    /// cache is dropped.
+    pub fn persist<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        match fs::read(path.as_ref()) {
+            Ok(bytes) => {
+                let entries: Vec<Entry> = serde_json::from_slice(&bytes)?;
+                let mut lru = self.lock();
+                for entry in entries {
+                    lru.insert(entry);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        self.path = Some(path.as_ref().to_owned());
+        Ok(self)
+    }
+
+    /// Returns the number of requests answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cacheable requests that had to be sent to the node.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        let mut lru = self.lock();
+        lru.entries.clear();
+        lru.order.clear();
+    }
+
+    /// Returns whether the block at `number` is deep enough, asking the node for its head if
+    /// the last known one is too low.
+    fn is_final(&self, request: &Request, number: u64) -> bool {
+        if number + self.confirmations <= self.head.load(Ordering::Relaxed) + 1 {
+            return true;
+        }
+        let head = Request {
+            method: "blockNumber",
+            params: &[],
+            id: request.id.clone(),
+            jsonrpc: request.jsonrpc,
+        };
+        match self
+            .inner
+            .send_request(&head)
+            .and_then(|res| res.into_result::<u64>())
+        {
+            Ok(head) => {
+                self.head.fetch_max(head, Ordering::Relaxed);
+                number + self.confirmations <= head + 1
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Updates the head from a transaction result and returns its confirmations.
+    fn observe_transaction(&self, result: &Value) -> u64 {
+        let confirmations = result["confirmations"].as_u64().unwrap_or(0);
+        if let Some(number) = result["blockNumber"].as_u64() {
+            self.head.fetch_max(
+                (number + confirmations).saturating_sub(1),
+                Ordering::Relaxed,
+            );
+        }
+        confirmations
+    }
+
+    /// Brings the confirmations of a cached transaction up to the last known head.
+    fn refresh_transaction(&self, result: &mut Value) {
+        let head = self.head.load(Ordering::Relaxed);
+        if let Some(number) = result["blockNumber"].as_u64() {
+            let confirmations = (head + 1).saturating_sub(number);
+            if confirmations > result["confirmations"].as_u64().unwrap_or(0) {
+                result["confirmations"] = confirmations.into();
+            }
+        }
+    }
+}
+
+impl<T: Transport> Transport for ResponseCache<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let lookup = match lookup(request.method) {
+            Some(lookup) => lookup,
+            None => {
+                let response = self.inner.send_request(request)?;
+                if request.method == "blockNumber" {
+                    if let Some(head) = response.result.as_ref().and_then(Value::as_u64) {
+                        self.head.fetch_max(head, Ordering::Relaxed);
+                    }
+                }
+                return Ok(response);
+            }
+        };
+
+        let key = key(request.method, request.params);
+        let cached = self.lock().get(&key);
+        if let Some(mut result) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            match lookup {
+                Lookup::Transaction => self.refresh_transaction(&mut result),
+                Lookup::Hash | Lookup::Number => {
+                    for transaction in block_transactions(&mut result) {
+                        self.refresh_transaction(transaction);
+                    }
+                }
+            }
+            return Ok(Response {
+                result: Some(result),
+                error: None,
+                id: request.id.clone(),
+                jsonrpc: Some("2.0".to_owned()),
+            });
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let response = self.inner.send_request(request)?;
+        let result = match (&response.result, &response.error) {
+            // Unknown hashes and numbers are answered with null, but may be known later.
+            (Some(result), None) if !result.is_null() => result,
+            _ => return Ok(response),
+        };
+        let cacheable = match lookup {
+            Lookup::Hash => true,
+            Lookup::Number => match request.params.first().and_then(Value::as_u64) {
+                Some(number) => self.is_final(request, number),
+                None => false,
+            },
+            Lookup::Transaction => self.observe_transaction(result) >= self.confirmations,
+        };
+        if cacheable {
+            self.lock().insert(Entry {
+                method: request.method.to_owned(),
+                params: request.params.to_vec(),
+                result: result.clone(),
+            });
+        }
+        Ok(response)
+    }
+}
+
+impl<T> ResponseCache<T> {
+    /// Writes the cache to the file passed to `persist`, from the least to the most recently
+    /// used result.
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let lru = self.lock();
+        let entries = lru
+            .order
+            .values()
+            .filter_map(|key| lru.entries.get(key))
+            .map(|(_, entry)| entry)
+            .collect::<Vec<_>>();
+        fs::write(path, serde_json::to_vec(&entries)?)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.lru.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T> Drop for ResponseCache<T> {
+    fn drop(&mut self) {
+        let _ = self.save();
+    }
+}
@@ -1,29 +1,209 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
 use jsonrpc::client::Client as RpcClient;
 use jsonrpc::error::Error;
+use jsonrpc::Request;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
+use crate::metrics::RequestMetrics;
 use crate::primitives::*;
+use crate::protocol::{self, Capability, NodeInfo};
+use crate::transport::Transport;
 
 pub struct Client {
-    agent: RpcClient,
+    agent: Box<dyn Transport>,
+    nonce: AtomicU64,
+    metrics: RequestMetrics,
+    node: Mutex<Option<NodeInfo>>,
+    /// Whether the node knows a method, learned from the responses so far.
+    methods: Mutex<HashMap<String, bool>>,
 }
 
 impl Client {
     pub fn new(host: &str) -> Client {
-        Client {
-            agent: RpcClient::new(host.to_owned(), None, None),
-        }
+        Client::new_with_transport(RpcClient::new(host.to_owned(), None, None))
     }
 
     pub fn new_with_credentials(host: &str, username: &str, password: &str) -> Client {
+        Client::new_with_transport(RpcClient::new(
+            host.to_owned(),
+            Some(username.to_owned()),
+            Some(password.to_owned()),
+        ))
+    }
+
+    /// Creates a client that sends its requests to a node on the same host over the Unix
+    /// domain socket at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `Path`: The socket the node serves its RPC on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// let client = Client::new_with_unix_socket("/var/run/nimiq/rpc.sock");
+    /// let result = client.block_number();
+    /// ```
+    #[cfg(unix)]
+    pub fn new_with_unix_socket<P: AsRef<std::path::Path>>(path: P) -> Client {
+        Client::new_with_transport(crate::unix::UnixSocketTransport::new(path))
+    }
+
+    /// Creates a client that sends its requests through the given transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `Transport`: The transport used to reach the node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// let agent = jsonrpc::client::Client::new("http://seed-host.com:8648".to_owned(), None, None);
+    /// let client = Client::new_with_transport(agent);
+    /// let result = client.block_number();
+    /// ```
+    pub fn new_with_transport<T: Transport + 'static>(transport: T) -> Client {
         Client {
-            agent: RpcClient::new(
-                host.to_owned(),
-                Some(username.to_owned()),
-                Some(password.to_owned()),
-            ),
+            agent: Box::new(transport),
+            nonce: AtomicU64::new(0),
+            metrics: RequestMetrics::default(),
+            node: Mutex::new(None),
+            methods: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Returns the call counts, error counts and latencies of the requests sent so far.
+    pub fn metrics(&self) -> &RequestMetrics {
+        &self.metrics
+    }
+
+    /// Returns which protocol the node speaks.
+    ///
+    /// The node is probed on the first call, later calls return the cached result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::protocol::Capability;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// if let Ok(node) = client.node_info() {
+    ///     println!("{} node, mining: {}", node.protocol, node.supports(Capability::ProofOfWork));
+    /// }
+    /// ```
+    pub fn node_info(&self) -> Result<NodeInfo, Error> {
+        if let Some(info) = &*self.node.lock().unwrap_or_else(|err| err.into_inner()) {
+            return Ok(info.clone());
+        }
+        self.probe()
+    }
+
+    /// Probes the node again, e.g. after it was upgraded, and caches the result.
+    pub fn probe(&self) -> Result<NodeInfo, Error> {
+        let info = protocol::probe(self)?;
+        *self.node.lock().unwrap_or_else(|err| err.into_inner()) = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Fails with an [`Unsupported`](protocol::Unsupported) error naming the node's protocol if
+    /// it lacks `capability`.
+    pub fn require(&self, capability: Capability) -> Result<(), Error> {
+        let info = self.node_info()?;
+        if info.supports(capability) {
+            Ok(())
+        } else {
+            Err(protocol::Unsupported {
+                protocol: info.protocol,
+                capability,
+            }
+            .into())
+        }
+    }
+
+    /// Returns whether the node knows `method`, or `None` if it was not called yet.
+    pub fn supports(&self, method: &str) -> Option<bool> {
+        self.methods
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(method)
+            .cloned()
+    }
+
+    pub(crate) fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[Value],
+    ) -> Result<T, Error> {
+        let request = Request {
+            method,
+            params,
+            id: Value::from(self.nonce.fetch_add(1, Ordering::SeqCst) + 1),
+            jsonrpc: Some("2.0"),
+        };
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "rpc",
+            method,
+            params_size = params
+                .iter()
+                .map(|param| param.to_string().len())
+                .sum::<usize>(),
+            duration_ms = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let start = Instant::now();
+        let result = self
+            .agent
+            .send_request(&request)
+            .and_then(|res| res.into_result::<T>());
+        let duration = start.elapsed();
+        self.metrics.record(method, duration, result.is_ok());
+        let known = match &result {
+            Ok(_) => Some(true),
+            Err(err) if protocol::is_method_not_found(err) => Some(false),
+            Err(Error::Rpc(_)) => Some(true),
+            Err(_) => None,
+        };
+        if let Some(known) = known {
+            self.methods
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(method.to_owned(), known);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("duration_ms", duration.as_secs_f64() * 1000.0);
+            match &result {
+                Ok(_) => {
+                    span.record("outcome", "ok");
+                    tracing::debug!("rpc call succeeded");
+                }
+                Err(Error::Rpc(err)) => {
+                    span.record("outcome", "rpc_error");
+                    tracing::warn!(code = err.code, message = %err.message, "rpc call failed");
+                }
+                Err(err) => {
+                    span.record("outcome", "error");
+                    tracing::warn!(error = %err, "rpc call failed");
+                }
+            }
+        }
+
+        result
+    }
+
     /// Returns a list of addresses owned by client.
     ///
     /// # Arguments
@@ -42,9 +222,7 @@ impl Client {
     /// let result = client.accounts();
     /// ```
     pub fn accounts(&self) -> Result<Vec<Account>, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("accounts", &[]))
-            .and_then(|res| res.into_result::<Vec<Account>>())
+        self.call("accounts", &[])
     }
 
     /// Returns the height of most recent block.
@@ -65,9 +243,7 @@ impl Client {
     /// let result = client.block_number();
     /// ```
     pub fn block_number(&self) -> Result<u64, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("blockNumber", &[]))
-            .and_then(|res| res.into_result::<u64>())
+        self.call("blockNumber", &[])
     }
 
     /// Returns information on the current consensus state.
@@ -88,9 +264,7 @@ impl Client {
     /// let result = client.consensus();
     /// ```
     pub fn consensus(&self) -> Result<String, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("consensus", &[]))
-            .and_then(|res| res.into_result::<String>())
+        self.call("consensus", &[])
     }
 
     /// Creates a new account and stores its private key in the client store.
@@ -111,9 +285,7 @@ impl Client {
     /// let result = client.create_account();
     /// ```
     pub fn create_account(&self) -> Result<Wallet, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("createAccount", &[]))
-            .and_then(|res| res.into_result::<Wallet>())
+        self.call("createAccount", &[])
     }
 
     /// Creates and signs a transaction without sending it. The transaction can then be send via `sendRawTransaction` without accidentally replaying it.
@@ -136,17 +308,15 @@ impl Client {
     ///    to: "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH3",
     ///    value: 100, //Lunas
     ///    fee: 0
-    ///	};
+    /// };
     /// let result = client.create_raw_transaction(&tx);
     /// ```
     pub fn create_raw_transaction(
         &self,
         raw_transaction: &OutgoingTransaction,
     ) -> Result<String, Error> {
-        let params = &[serde_json::to_value(&raw_transaction)?];
-        self.agent
-            .send_request(&self.agent.build_request("createRawTransaction", params))
-            .and_then(|res| res.into_result::<String>())
+        let params = &[serde_json::to_value(raw_transaction)?];
+        self.call("createRawTransaction", params)
     }
 
     /// Returns details for the account of given address.
@@ -168,9 +338,7 @@ impl Client {
     /// ```
     pub fn get_account(&self, id: &str) -> Result<Account, Error> {
         let params = &[serde_json::to_value(id)?];
-        self.agent
-            .send_request(&self.agent.build_request("getAccount", params))
-            .and_then(|res| res.into_result::<Account>())
+        self.call("getAccount", params)
     }
 
     /// Returns the balance of the account of given address.
@@ -192,9 +360,7 @@ impl Client {
     /// ```
     pub fn get_balance(&self, id: &str) -> Result<u64, Error> {
         let params = &[serde_json::to_value(id)?];
-        self.agent
-            .send_request(&self.agent.build_request("getBalance", params))
-            .and_then(|res| res.into_result::<u64>())
+        self.call("getBalance", params)
     }
 
     /// Returns information about a block by hash.
@@ -224,9 +390,7 @@ impl Client {
             serde_json::to_value(block_hash)?,
             serde_json::to_value(full_transactions)?,
         ];
-        self.agent
-            .send_request(&self.agent.build_request("getBlockByHash", params))
-            .and_then(|res| res.into_result::<Block>())
+        self.call("getBlockByHash", params)
     }
 
     /// Returns information about a block by block number.
@@ -256,9 +420,7 @@ impl Client {
             serde_json::to_value(block_number)?,
             serde_json::to_value(full_transactions)?,
         ];
-        self.agent
-            .send_request(&self.agent.build_request("getBlockByNumber", params))
-            .and_then(|res| res.into_result::<Block>())
+        self.call("getBlockByNumber", params)
     }
 
     /// Returns a template to build the next block for mining. This will consider pool instructions when connected to a pool.
@@ -279,9 +441,7 @@ impl Client {
     /// let result = client.get_block_template();
     /// ```
     pub fn get_block_template(&self) -> Result<FullBlock, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("getBlockTemplate", &[]))
-            .and_then(|res| res.into_result::<FullBlock>())
+        self.call("getBlockTemplate", &[])
     }
 
     /// Returns the number of transactions in a block from a block matching the given block hash.
@@ -303,13 +463,7 @@ impl Client {
     /// ```
     pub fn get_block_transaction_count_by_hash(&self, block_hash: &str) -> Result<u16, Error> {
         let params = &[serde_json::to_value(block_hash)?];
-        self.agent
-            .send_request(
-                &self
-                    .agent
-                    .build_request("getBlockTransactionCountByHash", params),
-            )
-            .and_then(|res| res.into_result::<u16>())
+        self.call("getBlockTransactionCountByHash", params)
     }
 
     /// Returns the number of transactions in a block matching the given block number.
@@ -331,13 +485,7 @@ impl Client {
     /// ```
     pub fn get_block_transaction_count_by_number(&self, block_number: u64) -> Result<u16, Error> {
         let params = &[serde_json::to_value(block_number)?];
-        self.agent
-            .send_request(
-                &self
-                    .agent
-                    .build_request("getBlockTransactionCountByNumber", params),
-            )
-            .and_then(|res| res.into_result::<u16>())
+        self.call("getBlockTransactionCountByNumber", params)
     }
 
     /// Returns information about a transaction by block hash and transaction index position.
@@ -367,13 +515,7 @@ impl Client {
             serde_json::to_value(block_hash)?,
             serde_json::to_value(index)?,
         ];
-        self.agent
-            .send_request(
-                &self
-                    .agent
-                    .build_request("getTransactionByBlockHashAndIndex", params),
-            )
-            .and_then(|res| res.into_result::<Transaction>())
+        self.call("getTransactionByBlockHashAndIndex", params)
     }
 
     /// Returns information about a transaction by block number and transaction index position.
@@ -403,13 +545,7 @@ impl Client {
             serde_json::to_value(block_number)?,
             serde_json::to_value(index)?,
         ];
-        self.agent
-            .send_request(
-                &self
-                    .agent
-                    .build_request("getTransactionByBlockNumberAndIndex", params),
-            )
-            .and_then(|res| res.into_result::<Transaction>())
+        self.call("getTransactionByBlockNumberAndIndex", params)
     }
 
     /// Returns the information about a transaction requested by transaction hash.
@@ -431,9 +567,7 @@ impl Client {
     /// ```
     pub fn get_transaction_by_hash(&self, transaction_hash: &str) -> Result<Transaction, Error> {
         let params = &[serde_json::to_value(transaction_hash)?];
-        self.agent
-            .send_request(&self.agent.build_request("getTransactionByHash", params))
-            .and_then(|res| res.into_result::<Transaction>())
+        self.call("getTransactionByHash", params)
     }
 
     /// Returns the receipt of a transaction by transaction hash.
@@ -459,9 +593,7 @@ impl Client {
         transaction_hash: &str,
     ) -> Result<TransactionReceipt, Error> {
         let params = &[serde_json::to_value(transaction_hash)?];
-        self.agent
-            .send_request(&self.agent.build_request("getTransactionReceipt", params))
-            .and_then(|res| res.into_result::<TransactionReceipt>())
+        self.call("getTransactionReceipt", params)
     }
 
     /// Returns the latest transactions successfully performed by or for an address.
@@ -493,9 +625,7 @@ impl Client {
             serde_json::to_value(address)?,
             serde_json::to_value(amount)?,
         ];
-        self.agent
-            .send_request(&self.agent.build_request("getTransactionsByAddress", params))
-            .and_then(|res| res.into_result::<Vec<Transaction>>())
+        self.call("getTransactionsByAddress", params)
     }
 
     /// Returns instructions to mine the next block. This will consider pool instructions when connected to a pool.
@@ -516,9 +646,7 @@ impl Client {
     /// let result = client.get_work();
     /// ```
     pub fn get_work(&self) -> Result<GetWork, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("getWork", &[]))
-            .and_then(|res| res.into_result::<GetWork>())
+        self.call("getWork", &[])
     }
 
     /// Returns the number of hashes per second that the node is mining with.
@@ -539,9 +667,7 @@ impl Client {
     /// let result = client.hashrate();
     /// ```
     pub fn hashrate(&self) -> Result<f64, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("hashrate", &[]))
-            .and_then(|res| res.into_result::<f64>())
+        self.call("hashrate", &[])
     }
 
     /// Sets the log level of the node.
@@ -564,47 +690,76 @@ impl Client {
     /// ```
     pub fn log(&self, tag: &str, level: &str) -> Result<bool, Error> {
         let params = &[serde_json::to_value(tag)?, serde_json::to_value(level)?];
-        self.agent
-            .send_request(&self.agent.build_request("log", params))
-            .and_then(|res| res.into_result::<bool>())
+        self.call("log", params)
+    }
+
+    /// Returns information on the current mempool situation. This will provide an overview of the number of transactions sorted into buckets based on their fee per byte (in smallest unit).
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// Mempool information, the number of transactions per fee bucket and the total number of transactions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.mempool();
+    /// ```
+    pub fn mempool(&self) -> Result<Mempool, Error> {
+        self.call("mempool", &[])
     }
 
     pub fn mempool_content(&self) -> Result<Vec<String>, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("mempoolContent", &[]))
-            .and_then(|res| res.into_result::<Vec<String>>())
+        self.call("mempoolContent", &[])
+    }
+
+    /// Returns the transactions that are currently in the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `none`
+    ///
+    /// # Returns
+    ///
+    /// Vector of pending transactions. `Note` Pending transactions have no block hash, block number and timestamp yet, these fields are left empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let result = client.mempool_content_with_transactions();
+    /// ```
+    pub fn mempool_content_with_transactions(&self) -> Result<Vec<Transaction>, Error> {
+        let params = &[serde_json::to_value(true)?];
+        self.call("mempoolContent", params)
     }
 
     pub fn miner_address(&self) -> Result<String, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("minerAddress", &[]))
-            .and_then(|res| res.into_result::<String>())
+        self.call("minerAddress", &[])
     }
 
     pub fn miner_threads(&self) -> Result<u8, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("minerThreads", &[]))
-            .and_then(|res| res.into_result::<u8>())
+        self.call("minerThreads", &[])
     }
 
     pub fn miner_threads_with_update(&self, threads: u16) -> Result<u16, Error> {
         let params = &[serde_json::to_value(threads)?];
-        self.agent
-            .send_request(&self.agent.build_request("minerThreads", params))
-            .and_then(|res| res.into_result::<u16>())
+        self.call("minerThreads", params)
     }
 
     pub fn min_fee_per_byte(&self) -> Result<u32, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("minFeePerByte", &[]))
-            .and_then(|res| res.into_result::<u32>())
+        self.call("minFeePerByte", &[])
     }
 
     pub fn min_fee_per_byte_with_update(&self, fee: u32) -> Result<u32, Error> {
         let params = &[serde_json::to_value(fee)?];
-        self.agent
-            .send_request(&self.agent.build_request("minFeePerByte", params))
-            .and_then(|res| res.into_result::<u32>())
+        self.call("minFeePerByte", params)
     }
 
     /// Returns `true` if client is actively mining new blocks.
@@ -625,9 +780,7 @@ impl Client {
     /// let result = client.mining();
     /// ```
     pub fn mining(&self) -> Result<bool, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("mining", &[]))
-            .and_then(|res| res.into_result::<bool>())
+        self.call("mining", &[])
     }
 
     /// Returns number of peers currently connected to the client.
@@ -648,22 +801,16 @@ impl Client {
     /// let result = client.peer_count();
     /// ```
     pub fn peer_count(&self) -> Result<i8, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("peerCount", &[]))
-            .and_then(|res| res.into_result::<i8>())
+        self.call("peerCount", &[])
     }
 
     pub fn peer_list(&self) -> Result<Vec<PeerList>, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("peerList", &[]))
-            .and_then(|res| res.into_result::<Vec<PeerList>>())
+        self.call("peerList", &[])
     }
 
     pub fn peer_state(&self, peer_address: &str) -> Result<PeerState, Error> {
         let params = &[serde_json::to_value(peer_address)?];
-        self.agent
-            .send_request(&self.agent.build_request("peerState", params))
-            .and_then(|res| res.into_result::<PeerState>())
+        self.call("peerState", params)
     }
 
     pub fn peer_state_with_update(
@@ -675,21 +822,15 @@ impl Client {
             serde_json::to_value(peer_address)?,
             serde_json::to_value(set)?,
         ];
-        self.agent
-            .send_request(&self.agent.build_request("peerState", params))
-            .and_then(|res| res.into_result::<PeerState>())
+        self.call("peerState", params)
     }
 
     pub fn pool_confirmed_balance(&self) -> Result<u64, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("poolConfirmedBalance", &[]))
-            .and_then(|res| res.into_result::<u64>())
+        self.call("poolConfirmedBalance", &[])
     }
 
     pub fn pool_connection_state(&self) -> Result<u8, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("poolConnectionState", &[]))
-            .and_then(|res| res.into_result::<u8>())
+        self.call("poolConnectionState", &[])
     }
 
     /// Sends a signed message call transaction or a contract creation, if the data field contains code.
@@ -712,15 +853,14 @@ impl Client {
     ///    to: "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH3",
     ///    value: 100, //Lunas
     ///    fee: 0
-    ///	};
-    /// let result = client.create_raw_transaction(&tx);
-    /// let hash = client.send_raw_transaction(&result);
+    /// };
+    /// if let Ok(raw) = client.create_raw_transaction(&tx) {
+    ///     let hash = client.send_raw_transaction(&raw);
+    /// }
     /// ```
     pub fn send_raw_transaction(&self, transaction_hash: &str) -> Result<String, Error> {
         let params = &[serde_json::to_value(transaction_hash)?];
-        self.agent
-            .send_request(&self.agent.build_request("sendRawTransaction", params))
-            .and_then(|res| res.into_result::<String>())
+        self.call("sendRawTransaction", params)
     }
 
     /// Creates new message call transaction or a contract creation, if the data field contains code.
@@ -743,14 +883,12 @@ impl Client {
     ///    to: "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH3",
     ///    value: 100, //Lunas
     ///    fee: 0
-    ///	};
+    /// };
     /// let result = client.send_transaction(&tx);
     /// ```
     pub fn send_transaction(&self, transaction: &OutgoingTransaction) -> Result<String, Error> {
         let params = &[serde_json::to_value(transaction)?];
-        self.agent
-            .send_request(&self.agent.build_request("sendTransaction", params))
-            .and_then(|res| res.into_result::<String>())
+        self.call("sendTransaction", params)
     }
 
     /// Submits a block to the node. When the block is valid, the node will forward it to other nodes in the network.
@@ -772,9 +910,7 @@ impl Client {
     /// ```
     pub fn submit_block(&self, full_block: &str) -> Result<(), Error> {
         let params = &[serde_json::to_value(full_block)?];
-        self.agent
-            .send_request(&self.agent.build_request("submitBlock", params))
-            .and_then(|res| res.into_result::<()>())
+        self.call("submitBlock", params)
     }
 
     /// Returns an object with data about the sync status or `false`.
@@ -795,8 +931,6 @@ impl Client {
     /// let result = client.syncing();
     /// ```
     pub fn syncing(&self) -> Result<Syncing, Error> {
-        self.agent
-            .send_request(&self.agent.build_request("syncing", &[]))
-            .and_then(|res| res.into_result::<Syncing>())
+        self.call("syncing", &[])
     }
 }
@@ -0,0 +1,257 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::metrics::escape;
+use crate::primitives::Syncing;
+use crate::Client;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Exposes the health of a node as Prometheus metrics.
+///
+/// The node is queried on every scrape, so the metrics are never older than the scrape itself.
+/// Calls that fail are left out of the scrape, `nimiq_up` tells whether the node answered at
+/// all. The latencies and errors of the exporter's own requests are included as well.
+///
+/// # Example
+///
+/// ```no_run
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::exporter::Exporter;
+/// let client = Client::new("http://seed-host.com:8648");
+/// Exporter::new(client).listen("0.0.0.0:9688").unwrap();
+/// ```
+pub struct Exporter {
+    client: Client,
+    namespace: String,
+    timeout: Duration,
+}
+
+impl Exporter {
+    /// Creates an exporter for the node behind `client`.
+    pub fn new(client: Client) -> Exporter {
+        Exporter {
+            client,
+            namespace: "nimiq".to_owned(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the prefix of all metric names, `nimiq` by default.
+    pub fn namespace(mut self, namespace: &str) -> Exporter {
+        self.namespace = namespace.to_owned();
+        self
+    }
+
+    /// Sets how long a scraper may take to send its request and read the answer, 5 seconds by
+    /// default.
+    pub fn timeout(mut self, timeout: Duration) -> Exporter {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Queries the node and returns the metrics in the Prometheus text format.
+    pub fn collect(&self) -> String {
+        let start = Instant::now();
+        let mut metrics = Metrics {
+            namespace: &self.namespace,
+            output: String::new(),
+        };
+
+        let block_number = self.client.block_number();
+        metrics.gauge(
+            "up",
+            "Whether the node answered the last scrape.",
+            block_number.is_ok() as u64,
+        );
+        if let Ok(block_number) = block_number {
+            metrics.gauge("block_number", "Height of the head block.", block_number);
+        }
+
+        if let Ok(consensus) = self.client.consensus() {
+            metrics.gauge(
+                "consensus_established",
+                "Whether the node has established consensus.",
+                (consensus == "established") as u64,
+            );
+        }
+
+        match self.client.syncing() {
+            Ok(Syncing::IsSyncing(syncing)) => {
+                metrics.gauge("syncing", "Whether the node is syncing.", syncing as u64)
+            }
+            Ok(Syncing::Pending(pending)) => {
+                metrics.gauge("syncing", "Whether the node is syncing.", 1);
+                metrics.gauge(
+                    "sync_highest_block",
+                    "Highest block known to the syncing node.",
+                    pending.highest_block,
+                );
+            }
+            Err(_) => {}
+        }
+
+        if let Ok(peer_count) = self.client.peer_count() {
+            metrics.gauge("peer_count", "Number of connected peers.", peer_count);
+        }
+
+        if let Ok(peers) = self.client.peer_list() {
+            let connected: Vec<_> = peers.iter().filter(|peer| peer.latency.is_some()).collect();
+            metrics.family(
+                "peer_latency_milliseconds",
+                "Latency of a connected peer.",
+                "gauge",
+                connected
+                    .iter()
+                    .map(|peer| (peer.address.as_str(), peer.latency.unwrap_or(0))),
+            );
+            metrics.family(
+                "peer_received_bytes_total",
+                "Bytes received from a connected peer.",
+                "counter",
+                connected
+                    .iter()
+                    .map(|peer| (peer.address.as_str(), peer.rx.unwrap_or(0))),
+            );
+            metrics.family(
+                "peer_sent_bytes_total",
+                "Bytes sent to a connected peer.",
+                "counter",
+                connected
+                    .iter()
+                    .map(|peer| (peer.address.as_str(), peer.tx.unwrap_or(0))),
+            );
+        }
+
+        if let Ok(hashrate) = self.client.hashrate() {
+            metrics.gauge(
+                "hashrate",
+                "Hashes per second of the node's miner.",
+                hashrate,
+            );
+        }
+        if let Ok(mining) = self.client.mining() {
+            metrics.gauge("mining", "Whether the node is mining.", mining as u64);
+        }
+        if let Ok(state) = self.client.pool_connection_state() {
+            metrics.gauge(
+                "pool_connection_state",
+                "Connection state of the mining pool, 0 connected, 1 connecting, 2 closed.",
+                state,
+            );
+        }
+        if let Ok(balance) = self.client.pool_confirmed_balance() {
+            metrics.gauge(
+                "pool_confirmed_balance_luna",
+                "Confirmed balance of the mining pool in Luna.",
+                balance,
+            );
+        }
+        if let Ok(mempool) = self.client.mempool() {
+            metrics.gauge(
+                "mempool_transactions",
+                "Number of transactions in the mempool.",
+                mempool.total,
+            );
+        }
+
+        metrics.gauge(
+            "scrape_duration_seconds",
+            "Time it took to query the node.",
+            start.elapsed().as_secs_f64(),
+        );
+        metrics.output + &self.client.metrics().to_prometheus(&self.namespace)
+    }
+
+    /// Serves the metrics on `/metrics` at the given address, blocking forever.
+    pub fn listen<A: ToSocketAddrs>(&self, address: A) -> io::Result<()> {
+        self.serve(TcpListener::bind(address)?)
+    }
+
+    /// Serves the metrics on `/metrics` for connections accepted by `listener`.
+    ///
+    /// Scrapes are answered one at a time, a connection that stays silent is dropped after the
+    /// timeout so it cannot hold up later scrapes.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            // A client hanging up should not take the exporter down.
+            let _ = self.handle(stream?);
+        }
+        Ok(())
+    }
+
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/metrics")) => ("200 OK", self.collect()),
+            (Some("GET"), Some(_)) => ("404 Not Found", "Not found, try /metrics\n".to_owned()),
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            CONTENT_TYPE,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+struct Metrics<'a> {
+    namespace: &'a str,
+    output: String,
+}
+
+impl<'a> Metrics<'a> {
+    fn gauge<T: ToString>(&mut self, name: &str, help: &str, value: T) {
+        self.header(name, help, "gauge");
+        let _ = writeln!(
+            self.output,
+            "{}_{} {}",
+            self.namespace,
+            name,
+            value.to_string()
+        );
+    }
+
+    /// Writes a metric with one sample per peer.
+    fn family<'p, I>(&mut self, name: &str, help: &str, kind: &str, samples: I)
+    where
+        I: IntoIterator<Item = (&'p str, u64)>,
+    {
+        self.header(name, help, kind);
+        for (peer, value) in samples {
+            let _ = writeln!(
+                self.output,
+                "{}_{}{{peer=\"{}\"}} {}",
+                self.namespace,
+                name,
+                escape(peer),
+                value
+            );
+        }
+    }
+
+    fn header(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.output, "# HELP {}_{} {}", self.namespace, name, help);
+        let _ = writeln!(self.output, "# TYPE {}_{} {}", self.namespace, name, kind);
+    }
+}
@@ -0,0 +1,168 @@
+use jsonrpc::error::Error;
+
+use crate::primitives::{Mempool, Transaction, TransactionSequence};
+use crate::Client;
+
+/// Maximum size of a block in bytes.
+pub const BLOCK_SIZE_MAX: usize = 100_000;
+
+/// Size of a basic transaction in bytes.
+pub const BASIC_TRANSACTION_SIZE: usize = 138;
+
+/// Size of an extended transaction with a signature proof and no data in bytes.
+pub const EXTENDED_TRANSACTION_SIZE: usize = 166;
+
+/// How fast a transaction should be included in a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeePriority {
+    /// Included in the next block.
+    NextBlock,
+    /// Included within the next five blocks.
+    WithinFiveBlocks,
+    /// Included eventually, at the lowest reasonable fee.
+    Economy,
+}
+
+/// Suggested fees per byte in Luna, per priority.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimate {
+    pub next_block: f64,
+    pub within_five_blocks: f64,
+    pub economy: f64,
+}
+
+impl FeeEstimate {
+    /// Returns the suggested fee per byte for the given priority.
+    pub fn fee_per_byte(&self, priority: FeePriority) -> f64 {
+        match priority {
+            FeePriority::NextBlock => self.next_block,
+            FeePriority::WithinFiveBlocks => self.within_five_blocks,
+            FeePriority::Economy => self.economy,
+        }
+    }
+
+    /// Returns the suggested total fee in Luna for a transaction of the given size in bytes.
+    pub fn total_fee(&self, priority: FeePriority, size: usize) -> u64 {
+        (self.fee_per_byte(priority) * size as f64).ceil() as u64
+    }
+}
+
+/// Estimates the fee needed to get a transaction included quickly.
+///
+/// The estimate combines the node's `minFeePerByte`, the fee buckets of the mempool and the
+/// fees paid in recent blocks. The mempool is assumed to be ordered by fee per byte; a priority
+/// of `n` blocks needs a fee that places the transaction within the first `n` blocks worth of
+/// mempool transactions. Recent blocks are only taken into account when they were nearly full,
+/// the lowest fee per byte included in those blocks is what it took to get in.
+pub struct FeeEstimator<'a> {
+    client: &'a Client,
+    sample_blocks: u64,
+}
+
+impl<'a> FeeEstimator<'a> {
+    /// Creates a fee estimator sampling the last 10 blocks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::fee::{FeeEstimator, FeePriority, BASIC_TRANSACTION_SIZE};
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let estimator = FeeEstimator::new(&client);
+    /// if let Ok(estimate) = estimator.estimate() {
+    ///     let fee = estimate.total_fee(FeePriority::NextBlock, BASIC_TRANSACTION_SIZE);
+    /// }
+    /// ```
+    pub fn new(client: &'a Client) -> FeeEstimator<'a> {
+        FeeEstimator {
+            client,
+            sample_blocks: 10,
+        }
+    }
+
+    /// Sets the number of recent blocks whose transactions are sampled.
+    pub fn sample_blocks(mut self, sample_blocks: u64) -> FeeEstimator<'a> {
+        self.sample_blocks = sample_blocks;
+        self
+    }
+
+    /// Queries the node and returns the suggested fees per byte.
+    pub fn estimate(&self) -> Result<FeeEstimate, Error> {
+        let floor = f64::from(self.client.min_fee_per_byte()?);
+        let mempool = self.client.mempool()?;
+
+        let head = self.client.block_number()?;
+        let mut minimums = Vec::new();
+        for number in head.saturating_sub(self.sample_blocks)..head {
+            let block = self.client.get_block_by_number(number + 1, true)?;
+            if (block.size as usize) < BLOCK_SIZE_MAX * 9 / 10 {
+                continue;
+            }
+            if let TransactionSequence::Transactions(transactions) = &block.transactions {
+                let minimum = transactions
+                    .iter()
+                    .map(fee_per_byte)
+                    .fold(f64::INFINITY, f64::min);
+                if minimum.is_finite() {
+                    minimums.push(minimum);
+                }
+            }
+        }
+        minimums.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        Ok(FeeEstimate {
+            next_block: floor
+                .max(mempool_fee(&mempool, 1))
+                .max(percentile(&minimums, 0.75)),
+            within_five_blocks: floor
+                .max(mempool_fee(&mempool, 5))
+                .max(percentile(&minimums, 0.5)),
+            economy: floor
+                .max(mempool_fee(&mempool, 20))
+                .max(percentile(&minimums, 0.0)),
+        })
+    }
+}
+
+/// Returns the size of a transaction in bytes.
+///
+/// Transactions without data and flags are counted as basic transactions, all others as
+/// extended transactions with a signature proof.
+pub fn transaction_size(transaction: &Transaction) -> usize {
+    match &transaction.data {
+        Some(data) if !data.is_empty() => EXTENDED_TRANSACTION_SIZE + data.len() / 2,
+        _ if transaction.flags != 0 => EXTENDED_TRANSACTION_SIZE,
+        _ => BASIC_TRANSACTION_SIZE,
+    }
+}
+
+/// Returns the fee per byte in Luna paid by a transaction.
+pub fn fee_per_byte(transaction: &Transaction) -> f64 {
+    transaction.fee as f64 / transaction_size(transaction) as f64
+}
+
+/// Returns the lowest bucket fee that still fits into the given number of blocks.
+fn mempool_fee(mempool: &Mempool, blocks: usize) -> f64 {
+    let capacity = blocks * BLOCK_SIZE_MAX / BASIC_TRANSACTION_SIZE;
+    let mut buckets = mempool.buckets.clone();
+    buckets.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut ahead = 0;
+    let mut previous = 0;
+    for bucket in buckets {
+        ahead += mempool.bucket(bucket) as usize;
+        if ahead >= capacity {
+            return f64::from(previous.max(bucket + 1));
+        }
+        previous = bucket;
+    }
+
+    0.0
+}
+
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    sorted[((sorted.len() - 1) as f64 * percentile).round() as usize]
+}
@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use jsonrpc::error::Error;
+
+use crate::primitives::Block;
+use crate::transport;
+use crate::Client;
+
+/// A change to the chain as seen by a [`ChainFollower`].
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    /// The block was appended to the followed chain.
+    Applied(Block),
+    /// The block was removed from the followed chain by a reorg.
+    Reverted(Block),
+    /// The block reached the configured confirmation depth and will not be reverted anymore.
+    Finalized(Block),
+}
+
+/// The error of a poll that found a reorg below the last finalized block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepReorg {
+    /// Number of the finalized block that is no longer on the main chain.
+    pub number: u64,
+    /// Hash of the finalized block.
+    pub hash: String,
+}
+
+impl DeepReorg {
+    /// Returns the `DeepReorg` wrapped in `err`, if it is one.
+    pub fn from_error(err: &Error) -> Option<&DeepReorg> {
+        transport::downcast_error(err)
+    }
+}
+
+impl fmt::Display for DeepReorg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "reorg below finalized block {} ({})",
+            self.number, self.hash
+        )
+    }
+}
+
+impl error::Error for DeepReorg {}
+
+impl From<DeepReorg> for Error {
+    fn from(reorg: DeepReorg) -> Error {
+        transport::typed_error(io::ErrorKind::InvalidData, reorg)
+    }
+}
+
+/// Follows the main chain of a node by polling `blockNumber` and `getBlockByNumber`.
+///
+/// Every new block is checked against the stored tip through its `parent_hash`. When the
+/// parent does not match, the tip is reverted and the follower walks back until it finds the
+/// fork point, so a reorg is reported as a number of `Reverted` events followed by the
+/// `Applied` events of the new branch.
+///
+/// While the head of the node is not above the tip, the block at the head is compared with the
+/// stored one instead, so that a replaced tip or a switch to a shorter chain is reported too.
+///
+/// Blocks are kept until they have `confirmations` confirmations, after which a `Finalized`
+/// event is emitted. A reorg that would revert the last finalized block fails the poll with a
+/// [`DeepReorg`] error, and so does every later poll, as the follower cannot switch chains
+/// without reverting a finalized block.
+pub struct ChainFollower<'a> {
+    client: &'a Client,
+    confirmations: u64,
+    full_transactions: bool,
+    poll_interval: Duration,
+    start_height: Option<u64>,
+    finalized: Option<Block>,
+    chain: VecDeque<Block>,
+    events: VecDeque<ChainEvent>,
+}
+
+impl<'a> ChainFollower<'a> {
+    /// Creates a follower that starts at the current head of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `&Client`: Client used to query the node.
+    /// * `Int`: Number of confirmations before a block is considered final.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::follower::ChainFollower;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let mut follower = ChainFollower::new(&client, 10);
+    /// let result = follower.poll();
+    /// ```
+    pub fn new(client: &'a Client, confirmations: u64) -> ChainFollower<'a> {
+        ChainFollower {
+            client,
+            confirmations,
+            full_transactions: false,
+            poll_interval: Duration::from_secs(1),
+            start_height: None,
+            finalized: None,
+            chain: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Starts following at the given height instead of the current head.
+    pub fn from_height(mut self, height: u64) -> ChainFollower<'a> {
+        self.start_height = Some(height);
+        self
+    }
+
+    /// Requests blocks with full transaction objects instead of transaction hashes.
+    pub fn full_transactions(mut self, full_transactions: bool) -> ChainFollower<'a> {
+        self.full_transactions = full_transactions;
+        self
+    }
+
+    /// Sets the delay between two polls when the follower is used as an iterator.
+    pub fn poll_interval(mut self, interval: Duration) -> ChainFollower<'a> {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Returns the most recent block of the followed chain.
+    pub fn tip(&self) -> Option<&Block> {
+        self.chain.back().or(self.finalized.as_ref())
+    }
+
+    /// Queries the node once and returns the events that happened since the last poll.
+    ///
+    /// # Returns
+    ///
+    /// Vector of chain events in the order they should be processed.
+    pub fn poll(&mut self) -> Result<Vec<ChainEvent>, Error> {
+        // A failed poll leaves the follower as it was, so no events are lost.
+        let chain = self.chain.clone();
+        let finalized = self.finalized.clone();
+        let start_height = self.start_height;
+
+        let result = self.advance();
+        if result.is_err() {
+            self.chain = chain;
+            self.finalized = finalized;
+            self.start_height = start_height;
+        }
+        result
+    }
+
+    fn advance(&mut self) -> Result<Vec<ChainEvent>, Error> {
+        let head = self.client.block_number()?;
+        let mut events = Vec::new();
+
+        let mut next = match self.tip() {
+            Some(tip) => tip.number + 1,
+            None => self.start_height.take().unwrap_or(head),
+        };
+
+        // The loop below only notices a reorg once the node is past the tip, so a replaced tip
+        // or a switch to a shorter chain is checked at the head of the node.
+        if let Some(stored) = self.block_at(head) {
+            let stored = stored.hash.clone();
+            let block = self
+                .client
+                .get_block_by_number(head, self.full_transactions)?;
+            if block.hash != stored {
+                self.revert_to(head, &mut events)?;
+                next = head;
+            }
+        }
+
+        while next <= head {
+            let block = self
+                .client
+                .get_block_by_number(next, self.full_transactions)?;
+
+            if let Some(tip) = self.tip() {
+                if tip.hash != block.parent_hash {
+                    let (number, hash) = (tip.number, tip.hash.clone());
+                    match self.chain.pop_back() {
+                        Some(tip) => {
+                            next = tip.number;
+                            events.push(ChainEvent::Reverted(tip));
+                            continue;
+                        }
+                        None => return Err(DeepReorg { number, hash }.into()),
+                    }
+                }
+            }
+
+            next = block.number + 1;
+            self.chain.push_back(block.clone());
+            events.push(ChainEvent::Applied(block));
+            self.finalize(&mut events);
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the stored block at `number`, if it is not below the last finalized block.
+    fn block_at(&self, number: u64) -> Option<&Block> {
+        self.chain
+            .iter()
+            .chain(self.finalized.as_ref())
+            .find(|block| block.number == number)
+    }
+
+    /// Reverts the blocks from `number` up to the tip.
+    fn revert_to(&mut self, number: u64, events: &mut Vec<ChainEvent>) -> Result<(), Error> {
+        while let Some(tip) = self.tip() {
+            if tip.number < number {
+                break;
+            }
+            let (number, hash) = (tip.number, tip.hash.clone());
+            match self.chain.pop_back() {
+                Some(tip) => events.push(ChainEvent::Reverted(tip)),
+                None => return Err(DeepReorg { number, hash }.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self, events: &mut Vec<ChainEvent>) {
+        let tip = match self.chain.back() {
+            Some(tip) => tip.number,
+            None => return,
+        };
+
+        while let Some(block) = self.chain.front() {
+            if tip - block.number + 1 < self.confirmations.max(1) {
+                break;
+            }
+            let block = self.chain.pop_front().unwrap();
+            self.finalized = Some(block.clone());
+            events.push(ChainEvent::Finalized(block));
+        }
+    }
+}
+
+impl<'a> Iterator for ChainFollower<'a> {
+    type Item = Result<ChainEvent, Error>;
+
+    /// Blocks until the next chain event is available, polling the node in between.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(Ok(event));
+            }
+
+            match self.poll() {
+                Ok(events) if events.is_empty() => thread::sleep(self.poll_interval),
+                Ok(events) => self.events.extend(events),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::{HashSet, VecDeque};
+
+use jsonrpc::error::Error;
+
+use crate::primitives::{Transaction, TransactionSequence};
+use crate::watcher::normalize;
+use crate::Client;
+
+/// Iterates over the complete transaction history of an address in chain order.
+///
+/// `getTransactionsByAddress` only returns the latest transactions of an address. When the
+/// node returns a full page, older transactions may be missing, so the blocks from the start
+/// height up to the oldest returned transaction are scanned with `getBlockByNumber`. Every
+/// transaction is yielded once, ordered by block number and index in the block.
+///
+/// Scanning blocks is slow, use `from_height` to resume from a height that is already known.
+pub struct TransactionHistory<'a> {
+    client: &'a Client,
+    address: String,
+    page_size: u16,
+    next_height: u64,
+    scan_until: Option<u64>,
+    recent: Option<Vec<Transaction>>,
+    queue: VecDeque<Transaction>,
+    seen: HashSet<String>,
+}
+
+impl<'a> TransactionHistory<'a> {
+    /// Creates an iterator over all transactions of the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `&Client`: Client used to query the node.
+    /// * `String`: Address of which transactions should be gathered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::history::TransactionHistory;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let history = TransactionHistory::new(&client, "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// let result = history.from_height(76415).next();
+    /// ```
+    pub fn new(client: &'a Client, address: &str) -> TransactionHistory<'a> {
+        TransactionHistory {
+            client,
+            address: address.to_owned(),
+            page_size: 1000,
+            next_height: 1,
+            scan_until: None,
+            recent: None,
+            queue: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Skips all transactions in blocks below the given height.
+    pub fn from_height(mut self, height: u64) -> TransactionHistory<'a> {
+        self.next_height = height.max(1);
+        self
+    }
+
+    /// Sets the number of transactions requested from `getTransactionsByAddress`.
+    pub fn page_size(mut self, page_size: u16) -> TransactionHistory<'a> {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Returns the height of the next block that will be scanned.
+    pub fn next_height(&self) -> u64 {
+        self.next_height
+    }
+
+    fn load_recent(&mut self) -> Result<(), Error> {
+        let page = self
+            .client
+            .get_transactions_by_address(&self.address, self.page_size)?;
+        let truncated = page.len() >= usize::from(self.page_size);
+
+        let mut recent: Vec<Transaction> =
+            page.into_iter().filter(|tx| tx.block_number > 0).collect();
+        recent.sort_by_key(|tx| (tx.block_number, tx.transaction_index));
+
+        // A full page may be cut off, everything up to its oldest block has to be scanned.
+        self.scan_until = match recent.first() {
+            Some(oldest) if truncated && oldest.block_number >= self.next_height => {
+                Some(oldest.block_number)
+            }
+            _ => Some(0),
+        };
+
+        let from = self.scan_until.unwrap_or(0).max(self.next_height - 1);
+        recent.retain(|tx| tx.block_number > from);
+        self.recent = Some(recent);
+        Ok(())
+    }
+
+    fn scan_block(&mut self, height: u64) -> Result<(), Error> {
+        let block = self.client.get_block_by_number(height, true)?;
+        let address = normalize(&self.address);
+
+        if let TransactionSequence::Transactions(mut transactions) = block.transactions {
+            transactions.sort_by_key(|tx| tx.transaction_index);
+            self.queue.extend(transactions.into_iter().filter(|tx| {
+                [&tx.from_address, &tx.from, &tx.to_address, &tx.to]
+                    .iter()
+                    .any(|candidate| normalize(candidate) == address)
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for TransactionHistory<'a> {
+    type Item = Result<Transaction, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(tx) = self.queue.pop_front() {
+                if self.seen.insert(tx.hash.clone()) {
+                    return Some(Ok(tx));
+                }
+                continue;
+            }
+
+            let scan_until = match self.scan_until {
+                Some(height) => height,
+                None => match self.load_recent() {
+                    Ok(()) => continue,
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+
+            if self.next_height <= scan_until {
+                if let Err(err) = self.scan_block(self.next_height) {
+                    return Some(Err(err));
+                }
+                self.next_height += 1;
+                continue;
+            }
+
+            let recent = self.recent.take()?;
+            if let Some(tx) = recent.last() {
+                self.next_height = tx.block_number + 1;
+            }
+            self.queue.extend(recent);
+        }
+    }
+}
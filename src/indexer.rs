@@ -0,0 +1,429 @@
+use std::fmt;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::primitives::{Block, Transaction, TransactionReceipt, TransactionSequence};
+use crate::watcher::normalize;
+use crate::Client;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS blocks (
+        number INTEGER PRIMARY KEY,
+        hash TEXT NOT NULL UNIQUE,
+        pow TEXT NOT NULL,
+        parent_hash TEXT NOT NULL,
+        nonce INTEGER NOT NULL,
+        body_hash TEXT NOT NULL,
+        accounts_hash TEXT NOT NULL,
+        miner TEXT NOT NULL,
+        miner_address TEXT NOT NULL,
+        difficulty TEXT NOT NULL,
+        extra_data TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS transactions (
+        hash TEXT PRIMARY KEY,
+        block_number INTEGER NOT NULL REFERENCES blocks (number) ON DELETE CASCADE,
+        block_hash TEXT NOT NULL,
+        transaction_index INTEGER,
+        timestamp INTEGER NOT NULL,
+        sender TEXT NOT NULL,
+        sender_address TEXT NOT NULL,
+        recipient TEXT NOT NULL,
+        recipient_address TEXT NOT NULL,
+        value INTEGER NOT NULL,
+        fee INTEGER NOT NULL,
+        data TEXT,
+        flags INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS transactions_block_number ON transactions (block_number);
+    CREATE INDEX IF NOT EXISTS transactions_sender_address ON transactions (sender_address);
+    CREATE INDEX IF NOT EXISTS transactions_recipient_address ON transactions (recipient_address);
+    CREATE VIEW IF NOT EXISTS receipts AS
+        SELECT hash AS transaction_hash, transaction_index, block_number, block_hash, timestamp
+        FROM transactions;
+";
+
+const TRANSACTION_COLUMNS: &str = "hash, block_number, block_hash, transaction_index, timestamp, \
+    sender, sender_address, recipient, recipient_address, value, fee, data, flags";
+
+/// Errors that can occur while indexing.
+#[derive(Debug)]
+pub enum IndexerError {
+    /// The node returned an error.
+    Rpc(jsonrpc::error::Error),
+    /// The database returned an error.
+    Sqlite(rusqlite::Error),
+}
+
+impl From<jsonrpc::error::Error> for IndexerError {
+    fn from(e: jsonrpc::error::Error) -> IndexerError {
+        IndexerError::Rpc(e)
+    }
+}
+
+impl From<rusqlite::Error> for IndexerError {
+    fn from(e: rusqlite::Error) -> IndexerError {
+        IndexerError::Sqlite(e)
+    }
+}
+
+impl fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexerError::Rpc(e) => write!(f, "RPC error: {}", e),
+            IndexerError::Sqlite(e) => write!(f, "SQLite error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IndexerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IndexerError::Rpc(e) => Some(e),
+            IndexerError::Sqlite(e) => Some(e),
+        }
+    }
+}
+
+/// Copies blocks and transactions of the main chain into a SQLite database.
+///
+/// Indexing resumes at the last stored height. Each block is checked against the stored
+/// parent, blocks of a fork that was abandoned by the node are removed again before the new
+/// branch is stored. Address lookups on the indexed range are answered from the database.
+pub struct Indexer<'a> {
+    client: &'a Client,
+    db: Connection,
+    start_height: u64,
+}
+
+impl<'a> Indexer<'a> {
+    /// Opens or creates the index database at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `&Client`: Client used to query the node.
+    /// * `Path`: Location of the SQLite database file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::indexer::Indexer;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let mut indexer = Indexer::open(&client, "chain.sqlite").unwrap();
+    /// let result = indexer.sync();
+    /// # std::fs::remove_file("chain.sqlite").unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(client: &'a Client, path: P) -> Result<Indexer<'a>, IndexerError> {
+        Indexer::with_connection(client, Connection::open(path)?)
+    }
+
+    /// Creates an index that only lives in memory.
+    pub fn open_in_memory(client: &'a Client) -> Result<Indexer<'a>, IndexerError> {
+        Indexer::with_connection(client, Connection::open_in_memory()?)
+    }
+
+    fn with_connection(client: &'a Client, db: Connection) -> Result<Indexer<'a>, IndexerError> {
+        db.execute_batch("PRAGMA foreign_keys = ON;")?;
+        db.execute_batch(SCHEMA)?;
+        Ok(Indexer {
+            client,
+            db,
+            start_height: 1,
+        })
+    }
+
+    /// Sets the height to start at when the database is empty.
+    pub fn from_height(mut self, height: u64) -> Indexer<'a> {
+        self.start_height = height.max(1);
+        self
+    }
+
+    /// Returns the height of the most recent indexed block.
+    pub fn height(&self) -> Result<Option<u64>, IndexerError> {
+        let height: Option<i64> =
+            self.db
+                .query_row("SELECT MAX(number) FROM blocks", [], |row| row.get(0))?;
+        Ok(height.map(|height| height as u64))
+    }
+
+    /// Indexes all blocks up to the current head of the node.
+    ///
+    /// # Returns
+    ///
+    /// The height of the most recent indexed block.
+    pub fn sync(&mut self) -> Result<u64, IndexerError> {
+        let head = self.client.block_number()?;
+        self.sync_to(head)
+    }
+
+    /// Indexes all blocks up to the given height.
+    ///
+    /// # Returns
+    ///
+    /// The height of the most recent indexed block.
+    pub fn sync_to(&mut self, height: u64) -> Result<u64, IndexerError> {
+        let mut next = match self.height()? {
+            Some(tip) => tip + 1,
+            None => self.start_height,
+        };
+
+        while next <= height {
+            let block = self.client.get_block_by_number(next, true)?;
+
+            if let Some(parent) = self.block_hash(next - 1)? {
+                if parent != block.parent_hash {
+                    self.db
+                        .execute("DELETE FROM blocks WHERE number >= ?1", params![next - 1])?;
+                    next -= 1;
+                    continue;
+                }
+            }
+
+            self.insert_block(&block)?;
+            next += 1;
+        }
+
+        Ok(next - 1)
+    }
+
+    fn block_hash(&self, number: u64) -> Result<Option<String>, IndexerError> {
+        Ok(self
+            .db
+            .query_row(
+                "SELECT hash FROM blocks WHERE number = ?1",
+                params![number],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn insert_block(&mut self, block: &Block) -> Result<(), IndexerError> {
+        let db = self.db.transaction()?;
+        db.execute(
+            "INSERT INTO blocks (number, hash, pow, parent_hash, nonce, body_hash, accounts_hash, \
+             miner, miner_address, difficulty, extra_data, size, timestamp) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                block.number,
+                block.hash,
+                block.pow,
+                block.parent_hash,
+                block.nonce,
+                block.body_hash,
+                block.accounts_hash,
+                block.miner,
+                block.miner_address,
+                block.difficulty,
+                block.extra_data,
+                block.size,
+                block.timestamp,
+            ],
+        )?;
+
+        if let TransactionSequence::Transactions(transactions) = &block.transactions {
+            for tx in transactions {
+                db.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO transactions ({}) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                        TRANSACTION_COLUMNS
+                    ),
+                    params![
+                        tx.hash,
+                        block.number,
+                        block.hash,
+                        tx.transaction_index,
+                        block.timestamp,
+                        normalize(&tx.from),
+                        normalize(&tx.from_address),
+                        normalize(&tx.to),
+                        normalize(&tx.to_address),
+                        tx.value,
+                        tx.fee,
+                        tx.data,
+                        tx.flags,
+                    ],
+                )?;
+            }
+        }
+
+        db.commit()?;
+        Ok(())
+    }
+
+    /// Returns an indexed block by hash. Transactions are returned as hashes.
+    pub fn block_by_hash(&self, block_hash: &str) -> Result<Option<Block>, IndexerError> {
+        self.block("hash = ?1", &block_hash.to_lowercase())
+    }
+
+    /// Returns an indexed block by number. Transactions are returned as hashes.
+    pub fn block_by_number(&self, block_number: u64) -> Result<Option<Block>, IndexerError> {
+        self.block("number = ?1", &block_number)
+    }
+
+    fn block(
+        &self,
+        condition: &str,
+        value: &dyn rusqlite::ToSql,
+    ) -> Result<Option<Block>, IndexerError> {
+        let block = self
+            .db
+            .query_row(
+                &format!(
+                    "SELECT number, hash, pow, parent_hash, nonce, body_hash, accounts_hash, \
+                     miner, miner_address, difficulty, extra_data, size, timestamp \
+                     FROM blocks WHERE {}",
+                    condition
+                ),
+                [value],
+                |row| {
+                    Ok(Block {
+                        number: row.get(0)?,
+                        hash: row.get(1)?,
+                        pow: row.get(2)?,
+                        parent_hash: row.get(3)?,
+                        nonce: row.get(4)?,
+                        body_hash: row.get(5)?,
+                        accounts_hash: row.get(6)?,
+                        miner: row.get(7)?,
+                        miner_address: row.get(8)?,
+                        difficulty: row.get(9)?,
+                        extra_data: row.get(10)?,
+                        size: row.get(11)?,
+                        timestamp: row.get(12)?,
+                        transactions: TransactionSequence::BlockHashes(Vec::new()),
+                    })
+                },
+            )
+            .optional()?;
+
+        match block {
+            Some(mut block) => {
+                let mut statement = self.db.prepare(
+                    "SELECT hash FROM transactions WHERE block_number = ?1 \
+                     ORDER BY transaction_index",
+                )?;
+                let hashes = statement
+                    .query_map(params![block.number], |row| row.get(0))?
+                    .collect::<Result<Vec<String>, _>>()?;
+                block.transactions = TransactionSequence::BlockHashes(hashes);
+                Ok(Some(block))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an indexed transaction by hash.
+    pub fn transaction_by_hash(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Option<Transaction>, IndexerError> {
+        let confirmations = self.height()?.unwrap_or(0) + 1;
+        Ok(self
+            .db
+            .query_row(
+                &format!(
+                    "SELECT {} FROM transactions WHERE hash = ?1",
+                    TRANSACTION_COLUMNS
+                ),
+                params![transaction_hash.to_lowercase()],
+                |row| transaction_from_row(row, confirmations),
+            )
+            .optional()?)
+    }
+
+    /// Returns the receipt of an indexed transaction.
+    pub fn transaction_receipt(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Option<TransactionReceipt>, IndexerError> {
+        let confirmations = self.height()?.unwrap_or(0) + 1;
+        Ok(self
+            .db
+            .query_row(
+                "SELECT transaction_hash, transaction_index, block_number, block_hash, timestamp \
+                 FROM receipts WHERE transaction_hash = ?1",
+                params![transaction_hash.to_lowercase()],
+                |row| {
+                    let block_number: u64 = row.get(2)?;
+                    Ok(TransactionReceipt {
+                        transaction_hash: row.get(0)?,
+                        transaction_index: row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+                        block_number,
+                        block_hash: row.get(3)?,
+                        confirmations: confirmations - block_number,
+                        timestamp: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Returns the latest indexed transactions performed by or for an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `String`: Address of which transactions should be gathered.
+    /// * `Int`: Number of transactions that shall be returned.
+    ///
+    /// # Returns
+    ///
+    /// Vector of transactions linked to the requested address, newest first.
+    pub fn transactions_by_address(
+        &self,
+        address: &str,
+        amount: u32,
+    ) -> Result<Vec<Transaction>, IndexerError> {
+        let confirmations = self.height()?.unwrap_or(0) + 1;
+        let (sender, recipient) = if address.trim().to_uppercase().starts_with("NQ") {
+            ("sender_address", "recipient_address")
+        } else {
+            ("sender", "recipient")
+        };
+
+        let mut statement = self.db.prepare(&format!(
+            "SELECT {} FROM transactions WHERE {} = ?1 OR {} = ?1 \
+             ORDER BY block_number DESC, transaction_index DESC LIMIT ?2",
+            TRANSACTION_COLUMNS, sender, recipient
+        ))?;
+        let transactions = statement
+            .query_map(params![normalize(address), amount], |row| {
+                transaction_from_row(row, confirmations)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(transactions)
+    }
+}
+
+fn transaction_from_row(row: &Row, confirmations: u64) -> rusqlite::Result<Transaction> {
+    let block_number: u64 = row.get(1)?;
+    Ok(Transaction {
+        hash: row.get(0)?,
+        block_number,
+        block_hash: row.get(2)?,
+        transaction_index: row.get(3)?,
+        timestamp: row.get(4)?,
+        confirmations: confirmations - block_number,
+        from: row.get(5)?,
+        from_address: format_address(&row.get::<_, String>(6)?),
+        to: row.get(7)?,
+        to_address: format_address(&row.get::<_, String>(8)?),
+        value: row.get(9)?,
+        fee: row.get(10)?,
+        data: row.get(11)?,
+        flags: row.get(12)?,
+    })
+}
+
+fn format_address(address: &str) -> String {
+    address
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,5 +1,33 @@
 mod client;
 
+pub mod address;
+pub mod albatross;
+pub mod block;
+pub mod breaker;
+pub mod cache;
+pub mod exporter;
+pub mod fee;
+pub mod follower;
+pub mod history;
+#[cfg(feature = "indexer")]
+pub mod indexer;
+pub mod merkle;
+pub mod metrics;
+#[cfg(feature = "mining")]
+pub mod mining;
+#[cfg(feature = "pow")]
+pub mod pow;
 pub mod primitives;
+pub mod protocol;
+pub mod ratelimit;
+pub mod superblock;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+#[cfg(unix)]
+pub mod unix;
+pub mod watcher;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 pub use self::client::Client;
@@ -0,0 +1,135 @@
+use std::convert::TryInto;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+
+use crate::block::{self, BlockBody, DecodeError, ADDRESS_SIZE, HASH_SIZE};
+use crate::primitives::{Block, Body, TransactionSequence};
+
+/// Returns the Blake2b-256 hash of `data`, the hash Nimiq uses for blocks, transactions and
+/// merkle trees.
+pub fn hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    Blake2b::<U32>::digest(data).into()
+}
+
+/// Computes the root of a merkle tree over already hashed leaves.
+///
+/// The leaves are split in half, with the left half taking the extra leaf of an odd count, and
+/// each inner node is the hash of its two children. An empty tree has the hash of no data as
+/// its root.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::merkle;
+/// let leaf = merkle::hash(b"leaf");
+/// assert_eq!(merkle::root(&[leaf]), leaf);
+/// assert_eq!(merkle::root(&[]), merkle::hash(b""));
+/// ```
+pub fn root(leaves: &[[u8; HASH_SIZE]]) -> [u8; HASH_SIZE] {
+    match leaves.len() {
+        0 => hash(&[]),
+        1 => leaves[0],
+        len => {
+            let (left, right) = leaves.split_at(len.div_ceil(2));
+            let mut node = [0; 2 * HASH_SIZE];
+            node[..HASH_SIZE].copy_from_slice(&root(left));
+            node[HASH_SIZE..].copy_from_slice(&root(right));
+            hash(&node)
+        }
+    }
+}
+
+/// Computes the body hash of a block, the merkle root over the miner address, the extra data,
+/// the transaction hashes and the serialized pruned accounts.
+pub fn body_hash(
+    miner_addr: &[u8; ADDRESS_SIZE],
+    extra_data: &[u8],
+    transaction_hashes: &[[u8; HASH_SIZE]],
+    pruned_accounts: &[Vec<u8>],
+) -> [u8; HASH_SIZE] {
+    let mut leaves = vec![hash(miner_addr), hash(extra_data)];
+    leaves.extend_from_slice(transaction_hashes);
+    leaves.extend(pruned_accounts.iter().map(|account| hash(account)));
+    root(&leaves)
+}
+
+/// Computes the body hash from the miner address, the extra data and the `merkleHashes` of a
+/// block template.
+///
+/// The merkle hashes are the siblings on the path from the node over the miner address and the
+/// extra data up to the root, from the bottom up. They let a miner change both without
+/// rehashing the transactions.
+pub fn body_hash_from_path(
+    miner_addr: &[u8; ADDRESS_SIZE],
+    extra_data: &[u8],
+    merkle_hashes: &[[u8; HASH_SIZE]],
+) -> [u8; HASH_SIZE] {
+    merkle_hashes.iter().fold(
+        root(&[hash(miner_addr), hash(extra_data)]),
+        |node, sibling| root(&[node, *sibling]),
+    )
+}
+
+/// Returns whether the body of a block template hashes to its `hash`.
+///
+/// The hash is rebuilt from the transactions and pruned accounts, and from the `merkleHashes`
+/// if the template has any.
+pub fn verify_body(body: &Body) -> Result<bool, DecodeError> {
+    let expected = block::hash(&body.hash, "hash")?;
+    let decoded = BlockBody::from_template(body)?;
+    if decoded.hash()? != expected {
+        return Ok(false);
+    }
+    if body.merkle_hashes.is_empty() {
+        return Ok(true);
+    }
+
+    let merkle_hashes = body
+        .merkle_hashes
+        .iter()
+        .map(|hash| block::hash(hash, "merkleHashes"))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(body_hash_from_path(&decoded.miner_addr, &decoded.extra_data, &merkle_hashes) == expected)
+}
+
+/// Returns whether the transactions of a block returned by the node hash to its `bodyHash`.
+///
+/// Either form of [`TransactionSequence`] can be checked, as only the transaction hashes are
+/// needed. The node does not return pruned accounts, so they have to be passed in serialized
+/// form for blocks that pruned any.
+///
+/// # Example
+///
+/// ```no_run
+/// use nimiq_rpc::{merkle, Client};
+/// let client = Client::new("http://seed-host.com:8648");
+/// let block = client.get_block_by_number(882418, true).unwrap();
+/// assert!(merkle::verify_block(&block, &[]).unwrap());
+/// ```
+pub fn verify_block(block: &Block, pruned_accounts: &[Vec<u8>]) -> Result<bool, DecodeError> {
+    let miner_addr = hex::decode(&block.miner)
+        .ok()
+        .and_then(|addr| addr.try_into().ok())
+        .ok_or(DecodeError::InvalidField("miner"))?;
+    let extra_data =
+        hex::decode(&block.extra_data).map_err(|_| DecodeError::InvalidField("extraData"))?;
+    let transaction_hashes = match &block.transactions {
+        TransactionSequence::BlockHashes(hashes) => hashes
+            .iter()
+            .map(|hash| block::hash(hash, "transactions"))
+            .collect::<Result<Vec<_>, _>>()?,
+        TransactionSequence::Transactions(transactions) => transactions
+            .iter()
+            .map(|transaction| block::hash(&transaction.hash, "transactions"))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let expected = block::hash(&block.body_hash, "bodyHash")?;
+    Ok(body_hash(
+        &miner_addr,
+        &extra_data,
+        &transaction_hashes,
+        pruned_accounts,
+    ) == expected)
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets in seconds.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Distribution of request durations over [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    /// Number of requests per bucket, not cumulative. The last entry counts the requests that
+    /// took longer than the largest bucket.
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    /// Number of observed requests.
+    pub count: u64,
+    /// Sum of all durations in seconds.
+    pub sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    /// Returns the mean duration in seconds.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+}
+
+/// Counters of a single RPC method.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodMetrics {
+    /// Number of calls, including failed ones.
+    pub calls: u64,
+    /// Number of calls that failed, either with an RPC error or in the transport.
+    pub errors: u64,
+    pub latency: Histogram,
+}
+
+/// Per-method call counts, error counts and latencies of a [`Client`](crate::Client).
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::Client;
+/// let client = Client::new("http://seed-host.com:8648");
+/// let result = client.block_number();
+/// let metrics = client.metrics().snapshot();
+/// assert_eq!(metrics["blockNumber"].calls, 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    methods: Mutex<HashMap<String, MethodMetrics>>,
+}
+
+impl RequestMetrics {
+    pub(crate) fn record(&self, method: &str, duration: Duration, success: bool) {
+        let mut methods = self.methods.lock().unwrap_or_else(|err| err.into_inner());
+        let metrics = match methods.get_mut(method) {
+            Some(metrics) => metrics,
+            None => methods.entry(method.to_owned()).or_default(),
+        };
+        metrics.calls += 1;
+        if !success {
+            metrics.errors += 1;
+        }
+        metrics.latency.observe(duration.as_secs_f64());
+    }
+
+    /// Returns the metrics of every method called so far, keyed by RPC method name.
+    pub fn snapshot(&self) -> HashMap<String, MethodMetrics> {
+        self.methods
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Clears all metrics.
+    pub fn reset(&self) {
+        self.methods
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    /// Renders the metrics in the Prometheus text format, with metric names prefixed by
+    /// `namespace`.
+    pub fn to_prometheus(&self, namespace: &str) -> String {
+        let mut methods: Vec<(String, MethodMetrics)> = self.snapshot().into_iter().collect();
+        methods.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "# HELP {0}_rpc_requests_total RPC requests sent per method.\n# TYPE {0}_rpc_requests_total counter",
+            namespace
+        );
+        for (method, metrics) in &methods {
+            let _ = writeln!(
+                output,
+                "{}_rpc_requests_total{{method=\"{}\"}} {}",
+                namespace,
+                escape(method),
+                metrics.calls
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP {0}_rpc_errors_total Failed RPC requests per method.\n# TYPE {0}_rpc_errors_total counter",
+            namespace
+        );
+        for (method, metrics) in &methods {
+            let _ = writeln!(
+                output,
+                "{}_rpc_errors_total{{method=\"{}\"}} {}",
+                namespace,
+                escape(method),
+                metrics.errors
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP {0}_rpc_request_duration_seconds Duration of RPC requests per method.\n# TYPE {0}_rpc_request_duration_seconds histogram",
+            namespace
+        );
+        for (method, metrics) in &methods {
+            let method = escape(method);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(&metrics.latency.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    output,
+                    "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    namespace, method, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                output,
+                "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                namespace, method, metrics.latency.count
+            );
+            let _ = writeln!(
+                output,
+                "{}_rpc_request_duration_seconds_sum{{method=\"{}\"}} {}",
+                namespace, method, metrics.latency.sum
+            );
+            let _ = writeln!(
+                output,
+                "{}_rpc_request_duration_seconds_count{{method=\"{}\"}} {}",
+                namespace, method, metrics.latency.count
+            );
+        }
+
+        output
+    }
+}
+
+/// Escapes a Prometheus label value.
+pub(crate) fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
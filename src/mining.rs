@@ -0,0 +1,290 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use jsonrpc::error::Error;
+
+use crate::block::BlockHeader;
+use crate::pow::{self, Hasher, HASH_SIZE};
+use crate::primitives::GetWork;
+use crate::protocol::Capability;
+use crate::transport;
+use crate::Client;
+
+/// Offset of the block height in a serialized block header.
+const HEIGHT_OFFSET: usize = 134;
+
+/// Offset of the nonce in a serialized block header.
+const NONCE_OFFSET: usize = 142;
+
+/// The error of `getWork` data that cannot be mined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidWork {
+    /// What is wrong with the work.
+    pub reason: String,
+}
+
+impl InvalidWork {
+    fn new(reason: &str) -> InvalidWork {
+        InvalidWork {
+            reason: reason.to_owned(),
+        }
+    }
+
+    /// Returns the `InvalidWork` wrapped in `err`, if it is one.
+    pub fn from_error(err: &Error) -> Option<&InvalidWork> {
+        transport::downcast_error(err)
+    }
+}
+
+impl fmt::Display for InvalidWork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid work: {}", self.reason)
+    }
+}
+
+impl error::Error for InvalidWork {}
+
+impl From<InvalidWork> for Error {
+    fn from(invalid: InvalidWork) -> Error {
+        transport::typed_error(io::ErrorKind::InvalidData, invalid)
+    }
+}
+
+/// A block template to find a nonce for, as returned by `getWork`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Work {
+    header: Vec<u8>,
+    suffix: Vec<u8>,
+    target: [u8; HASH_SIZE],
+}
+
+impl Work {
+    /// Decodes the result of `getWork`.
+    ///
+    /// Fails if the algorithm is not nimiq-argon2d or the header is malformed.
+    pub fn new(work: &GetWork) -> Result<Work, InvalidWork> {
+        if !work.algorithm.starts_with("nimiq-argon2") {
+            return Err(InvalidWork {
+                reason: format!("unsupported algorithm {}", work.algorithm),
+            });
+        }
+        let header = hex::decode(&work.data).map_err(|_| InvalidWork::new("data is not hex"))?;
+        if header.len() != BlockHeader::SIZE {
+            return Err(InvalidWork::new("header has the wrong size"));
+        }
+        let suffix =
+            hex::decode(&work.suffix).map_err(|_| InvalidWork::new("suffix is not hex"))?;
+        if work.target > u64::from(u32::MAX) {
+            return Err(InvalidWork::new("target is not in compact form"));
+        }
+
+        Ok(Work {
+            header,
+            suffix,
+            target: pow::compact_to_target(work.target as u32),
+        })
+    }
+
+    /// Returns the height of the block being mined.
+    pub fn height(&self) -> u64 {
+        let mut height = [0; 4];
+        height.copy_from_slice(&self.header[HEIGHT_OFFSET..HEIGHT_OFFSET + 4]);
+        u64::from(u32::from_be_bytes(height))
+    }
+
+    /// Returns the hex encoded block with the given nonce, ready for `submitBlock`.
+    pub fn block(&self, nonce: u32) -> String {
+        let mut block = self.header.clone();
+        block[NONCE_OFFSET..].copy_from_slice(&nonce.to_be_bytes());
+        block.extend_from_slice(&self.suffix);
+        hex::encode(block)
+    }
+}
+
+/// A block that met its target and was submitted to the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinedBlock {
+    pub height: u64,
+    pub nonce: u32,
+    /// Hex encoded proof-of-work hash of the header.
+    pub pow: String,
+    /// Hex encoded block as submitted.
+    pub block: String,
+}
+
+/// Stops a running [`Miner`] from another thread.
+#[derive(Debug, Clone)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A CPU miner that fetches work with `getWork` and submits found blocks with `submitBlock`.
+///
+/// The nonce space is split across the configured number of threads. While searching, the
+/// miner polls `blockNumber` and abandons the work as soon as a block at the height being mined
+/// appears, so that no time is spent on a stale template.
+///
+/// # Example
+///
+/// ```no_run
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::mining::Miner;
+/// let client = Client::new("http://seed-host.com:8648");
+/// let miner = Miner::new(&client).threads(4);
+/// let stop = miner.stop_handle();
+/// std::thread::spawn(move || {
+///     std::thread::sleep(std::time::Duration::from_secs(60));
+///     stop.stop();
+/// });
+/// let blocks = miner.run().unwrap();
+/// println!("found {} blocks at {} H/s", blocks, miner.hashrate());
+/// ```
+pub struct Miner<'a> {
+    client: &'a Client,
+    threads: usize,
+    poll_interval: Duration,
+    stopped: Arc<AtomicBool>,
+    hashes: AtomicU64,
+    blocks: AtomicU64,
+    started: Instant,
+}
+
+impl<'a> Miner<'a> {
+    /// Creates a miner with one thread per available CPU.
+    pub fn new(client: &'a Client) -> Miner<'a> {
+        Miner {
+            client,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            poll_interval: Duration::from_secs(1),
+            stopped: Arc::new(AtomicBool::new(false)),
+            hashes: AtomicU64::new(0),
+            blocks: AtomicU64::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// Sets the number of hashing threads.
+    pub fn threads(mut self, threads: usize) -> Miner<'a> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets how often the node is asked for a new head while searching.
+    pub fn poll_interval(mut self, interval: Duration) -> Miner<'a> {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Returns a handle that stops the miner.
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle(Arc::clone(&self.stopped))
+    }
+
+    /// Returns the number of hashes computed so far.
+    pub fn hashes(&self) -> u64 {
+        self.hashes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the average number of hashes per second since the miner was created.
+    pub fn hashrate(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.hashes() as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the number of blocks found so far.
+    pub fn blocks_found(&self) -> u64 {
+        self.blocks.load(Ordering::Relaxed)
+    }
+
+    /// Mines until the miner is stopped and returns the number of blocks found.
+    pub fn run(&self) -> Result<u64, Error> {
+        let found = self.blocks_found();
+        while !self.stopped.load(Ordering::SeqCst) {
+            self.mine_block()?;
+        }
+        Ok(self.blocks_found() - found)
+    }
+
+    /// Fetches work, searches for a nonce and submits the block if one is found.
+    ///
+    /// Returns `None` if the search was abandoned because a new head appeared, the nonce space
+    /// was exhausted or the miner was stopped. Fails if the node does not speak proof of work, and
+    /// with an [`InvalidWork`] error if its work cannot be mined.
+    pub fn mine_block(&self) -> Result<Option<MinedBlock>, Error> {
+        self.client.require(Capability::ProofOfWork)?;
+        let work = Work::new(&self.client.get_work()?)?;
+        let (nonce, hash) = match self.search(&work) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        let block = work.block(nonce);
+        self.client.submit_block(&block)?;
+        self.blocks.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(MinedBlock {
+            height: work.height(),
+            nonce,
+            pow: hex::encode(hash),
+            block,
+        }))
+    }
+
+    /// Searches the nonce space of `work` for a hash that meets its target.
+    pub fn search(&self, work: &Work) -> Option<(u32, [u8; HASH_SIZE])> {
+        let found = Mutex::new(None);
+        let cancelled = AtomicBool::new(false);
+        let running = AtomicUsize::new(self.threads);
+        let done = || cancelled.load(Ordering::SeqCst) || self.stopped.load(Ordering::SeqCst);
+
+        thread::scope(|scope| {
+            for first in 0..self.threads {
+                let (found, cancelled, running) = (&found, &cancelled, &running);
+                scope.spawn(move || {
+                    let mut hasher = Hasher::new();
+                    let mut header = work.header.clone();
+                    let mut nonce = first as u64;
+                    while nonce <= u64::from(u32::MAX) && !done() {
+                        header[NONCE_OFFSET..].copy_from_slice(&(nonce as u32).to_be_bytes());
+                        let hash = hasher.hash(&header);
+                        self.hashes.fetch_add(1, Ordering::Relaxed);
+                        if pow::meets_target(&hash, &work.target) {
+                            *found.lock().unwrap() = Some((nonce as u32, hash));
+                            cancelled.store(true, Ordering::SeqCst);
+                        }
+                        nonce += self.threads as u64;
+                    }
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+
+            let mut polled = Instant::now();
+            while running.load(Ordering::SeqCst) > 0 && !done() {
+                thread::sleep(self.poll_interval.min(Duration::from_millis(50)));
+                if polled.elapsed() < self.poll_interval {
+                    continue;
+                }
+                polled = Instant::now();
+                if let Ok(head) = self.client.block_number() {
+                    if head >= work.height() {
+                        cancelled.store(true, Ordering::SeqCst);
+                    }
+                }
+            }
+        });
+
+        found.into_inner().unwrap()
+    }
+}
@@ -0,0 +1,180 @@
+use argon2::{Algorithm, Argon2, Block, Params, Version};
+
+pub use crate::block::HASH_SIZE;
+use crate::block::{self, BlockHeader, DecodeError};
+use crate::primitives;
+
+/// Compact form of the largest target, which has a difficulty of 1.
+pub const BLOCK_TARGET_MAX_COMPACT: u32 = 0x1f01_0000;
+
+/// The largest target as a floating point number, 2^240.
+const BLOCK_TARGET_MAX: f64 = 1.766_847_064_778_384_3e72;
+
+/// Memory used by a single nimiq-argon2d hash in KiB.
+const MEMORY_COST: u32 = 512;
+
+const SALT: &[u8] = b"nimiqrocks!";
+
+/// Computes nimiq-argon2d hashes, reusing its memory between hashes.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::pow::Hasher;
+/// let mut hasher = Hasher::new();
+/// assert_eq!(hasher.hash(b"header"), nimiq_rpc::pow::hash(b"header"));
+/// ```
+pub struct Hasher {
+    argon2: Argon2<'static>,
+    memory: Vec<Block>,
+}
+
+impl Hasher {
+    pub fn new() -> Hasher {
+        let params = Params::new(MEMORY_COST, 1, 1, Some(HASH_SIZE)).unwrap();
+        Hasher {
+            memory: vec![Block::default(); params.block_count()],
+            argon2: Argon2::new(Algorithm::Argon2d, Version::V0x13, params),
+        }
+    }
+
+    /// Returns the nimiq-argon2d hash of `data`, usually a serialized block header.
+    pub fn hash(&mut self, data: &[u8]) -> [u8; HASH_SIZE] {
+        let mut hash = [0; HASH_SIZE];
+        self.argon2
+            .hash_password_into_with_memory(data, SALT, &mut hash, &mut self.memory)
+            .unwrap();
+        hash
+    }
+}
+
+impl Default for Hasher {
+    fn default() -> Hasher {
+        Hasher::new()
+    }
+}
+
+/// Returns the nimiq-argon2d hash of `data`.
+///
+/// Use a [`Hasher`] to compute many hashes.
+pub fn hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    Hasher::new().hash(data)
+}
+
+/// Expands a target in compact form, as found in the `nBits` field of a block header, into a
+/// 256 bit big-endian number.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::pow;
+/// let target = pow::compact_to_target(0x1f010000);
+/// assert_eq!(target[1], 1);
+/// assert!(target[2..].iter().all(|byte| *byte == 0));
+/// ```
+pub fn compact_to_target(compact: u32) -> [u8; HASH_SIZE] {
+    let exponent = (compact >> 24) as i64;
+    let mantissa = compact & 0x00ff_ffff;
+
+    let mut target = [0; HASH_SIZE];
+    for k in 0..3 {
+        // Position of the byte counted from the least significant end.
+        let index = k + exponent - 3;
+        if (0..HASH_SIZE as i64).contains(&index) {
+            target[HASH_SIZE - 1 - index as usize] = (mantissa >> (8 * k)) as u8;
+        }
+    }
+    target
+}
+
+/// Returns whether a proof-of-work hash satisfies the given target.
+pub fn meets_target(hash: &[u8; HASH_SIZE], target: &[u8; HASH_SIZE]) -> bool {
+    hash <= target
+}
+
+/// Compresses a 256 bit big-endian target into its compact form.
+///
+/// The compact form keeps the three most significant bytes, so the conversion loses precision
+/// for targets with more significant bits.
+pub fn target_to_compact(target: &[u8; HASH_SIZE]) -> u32 {
+    let mut size = target
+        .iter()
+        .position(|byte| *byte != 0)
+        .map_or(1, |first| HASH_SIZE - first);
+    // The mantissa is signed, a leading byte with the highest bit set needs an extra byte.
+    if target[HASH_SIZE - size] >= 0x80 {
+        size += 1;
+    }
+
+    let byte = |index: i64| match index {
+        0..=31 => u32::from(target[HASH_SIZE - 1 - index as usize]),
+        _ => 0,
+    };
+    let mantissa = (0..3).fold(0, |mantissa, k| {
+        mantissa | byte(k + size as i64 - 3) << (8 * k)
+    });
+    (size as u32) << 24 | mantissa
+}
+
+/// Returns the difficulty of a target, 2^240 divided by the target.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::pow;
+/// let target = pow::compact_to_target(pow::BLOCK_TARGET_MAX_COMPACT);
+/// assert_eq!(pow::target_to_difficulty(&target), 1.0);
+/// ```
+pub fn target_to_difficulty(target: &[u8; HASH_SIZE]) -> f64 {
+    BLOCK_TARGET_MAX / to_f64(target)
+}
+
+/// Returns the target of a difficulty, 2^240 divided by the difficulty.
+///
+/// The target is exact to about 53 significant bits.
+pub fn difficulty_to_target(difficulty: f64) -> [u8; HASH_SIZE] {
+    let mut rest = BLOCK_TARGET_MAX / difficulty;
+    let mut target = [0; HASH_SIZE];
+    for (i, byte) in target.iter_mut().enumerate() {
+        let scale = 256f64.powi((HASH_SIZE - 1 - i) as i32);
+        let value = (rest / scale).floor().clamp(0.0, 255.0);
+        *byte = value as u8;
+        rest -= value * scale;
+    }
+    target
+}
+
+/// Returns the proof-of-work hash of a block header.
+pub fn header_hash(header: &BlockHeader) -> [u8; HASH_SIZE] {
+    hash(&header.serialize())
+}
+
+/// Returns whether the proof-of-work hash of a header meets the target of its `n_bits`.
+///
+/// Only the header itself is checked, the caller has to verify that `n_bits` is the target the
+/// chain requires at that height.
+pub fn verify_header(header: &BlockHeader) -> bool {
+    meets_target(&header_hash(header), &compact_to_target(header.n_bits))
+}
+
+/// Returns whether the `pow` reported for a block meets the target of its reported difficulty.
+///
+/// The RPC block lacks the interlink hash and `nBits`, so the hash cannot be recomputed from
+/// it; use [`verify_header`] with the serialized header to check the hash itself.
+pub fn verify_block(block: &primitives::Block) -> Result<bool, DecodeError> {
+    let pow = block::hash(&block.pow, "pow")?;
+    let difficulty: f64 = block
+        .difficulty
+        .parse()
+        .map_err(|_| DecodeError::InvalidField("difficulty"))?;
+    if !(difficulty > 0.0 && difficulty.is_finite()) {
+        return Err(DecodeError::InvalidField("difficulty"));
+    }
+    Ok(meets_target(&pow, &difficulty_to_target(difficulty)))
+}
+
+fn to_f64(value: &[u8; HASH_SIZE]) -> f64 {
+    value
+        .iter()
+        .fold(0.0, |result, byte| result * 256.0 + f64::from(*byte))
+}
@@ -1,12 +1,14 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Address {
     pub id: String,
     pub address: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     pub id: String,
     pub address: String,
@@ -14,7 +16,7 @@ pub struct Account {
     pub r#type: u8,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
     pub number: u64,
@@ -55,12 +57,12 @@ pub struct Header {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Body {
-    hash: String,
-    miner_addr: String,
-    extra_data: String,
-    transactions: Vec<String>,
-    merkle_hashes: Vec<String>,
-    pruned_accounts: Vec<String>,
+    pub hash: String,
+    pub miner_addr: String,
+    pub extra_data: String,
+    pub transactions: Vec<String>,
+    pub merkle_hashes: Vec<String>,
+    pub pruned_accounts: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,6 +73,24 @@ pub struct GetWork {
     pub algorithm: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mempool {
+    pub total: u64,
+    pub buckets: Vec<u32>,
+    #[serde(flatten)]
+    pub transactions_per_bucket: HashMap<String, u64>,
+}
+
+impl Mempool {
+    /// Returns the number of transactions in the bucket with the given minimum fee per byte.
+    pub fn bucket(&self, fee_per_byte: u32) -> u64 {
+        self.transactions_per_bucket
+            .get(&fee_per_byte.to_string())
+            .cloned()
+            .unwrap_or(0)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PeerList {
@@ -94,7 +114,7 @@ pub struct PeerState {
     pub address_state: u8,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Syncing {
     IsSyncing(bool),
@@ -104,17 +124,20 @@ pub enum Syncing {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Pending {
-    starting_block: u64,
-    current_block: u64,
-    highest_block: u64,
+    pub starting_block: u64,
+    pub current_block: u64,
+    pub highest_block: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
     pub hash: String,
+    #[serde(default)]
     pub block_hash: String,
+    #[serde(default)]
     pub block_number: u64,
+    #[serde(default)]
     pub timestamp: u64,
     pub confirmations: u64,
     pub transaction_index: Option<i64>,
@@ -139,7 +162,7 @@ pub struct TransactionReceipt {
     pub timestamp: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]
 pub enum TransactionSequence {
@@ -148,14 +171,14 @@ pub enum TransactionSequence {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct OutgoingTransaction {
-    pub from: &'static str,
-    pub to: &'static str,
+pub struct OutgoingTransaction<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
     pub value: u64,
     pub fee: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Wallet {
     pub id: String,
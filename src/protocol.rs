@@ -0,0 +1,150 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use jsonrpc::error::{Error, RpcError};
+use serde_json::Value;
+
+use crate::transport;
+use crate::Client;
+
+/// JSON-RPC error code of a method the node does not know.
+pub const METHOD_NOT_FOUND: i32 = -32601;
+
+/// The RPC dialect spoken by a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// Nimiq 1.x proof-of-work nodes, such as core-js.
+    Legacy,
+    /// Nimiq 2.0 proof-of-stake nodes, such as core-rs-albatross.
+    Albatross,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Protocol::Legacy => write!(f, "Nimiq 1.x"),
+            Protocol::Albatross => write!(f, "Nimiq 2.0 (Albatross)"),
+        }
+    }
+}
+
+/// A group of RPC methods that only some nodes offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Proof-of-work blocks, block templates and `getWork`, used by the `mining` and `block`
+    /// helpers.
+    ProofOfWork,
+    /// Validators, stakers, epochs and batches.
+    ProofOfStake,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Capability::ProofOfWork => write!(f, "proof of work"),
+            Capability::ProofOfStake => write!(f, "proof of stake"),
+        }
+    }
+}
+
+/// What a node identified itself as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub protocol: Protocol,
+    /// Protocol version, only reported by Albatross nodes.
+    pub version: Option<u16>,
+}
+
+impl NodeInfo {
+    /// Returns whether the node offers the methods of `capability`.
+    pub fn supports(&self, capability: Capability) -> bool {
+        matches!(
+            (self.protocol, capability),
+            (Protocol::Legacy, Capability::ProofOfWork)
+                | (Protocol::Albatross, Capability::ProofOfStake)
+        )
+    }
+}
+
+/// The error of a call that needs a capability the node lacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsupported {
+    /// What the node identified itself as.
+    pub protocol: Protocol,
+    pub capability: Capability,
+}
+
+impl Unsupported {
+    /// Returns the `Unsupported` wrapped in `err`, if it is one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::protocol::{Capability, Unsupported};
+    /// # let client = nimiq_rpc::Client::new("http://seed-host.com:8648");
+    /// if let Err(err) = client.require(Capability::ProofOfWork) {
+    ///     if let Some(unsupported) = Unsupported::from_error(&err) {
+    ///         println!("cannot mine on a {} node", unsupported.protocol);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_error(err: &Error) -> Option<&Unsupported> {
+        transport::downcast_error(err)
+    }
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes do not support {}",
+            self.protocol, self.capability
+        )
+    }
+}
+
+impl error::Error for Unsupported {}
+
+impl From<Unsupported> for Error {
+    fn from(unsupported: Unsupported) -> Error {
+        transport::typed_error(io::ErrorKind::Unsupported, unsupported)
+    }
+}
+
+/// Identifies the node behind `client` by the block number methods it knows.
+pub(crate) fn probe(client: &Client) -> Result<NodeInfo, Error> {
+    match client.call::<Value>("getBlockNumber", &[]) {
+        Ok(_) => {
+            let version = client
+                .call::<Value>("getPolicyConstants", &[])
+                .ok()
+                .and_then(|constants| constants["data"]["version"].as_u64())
+                .map(|version| version as u16);
+            return Ok(NodeInfo {
+                protocol: Protocol::Albatross,
+                version,
+            });
+        }
+        Err(err) if !is_method_not_found(&err) => return Err(err),
+        Err(_) => {}
+    }
+
+    match client.call::<Value>("blockNumber", &[]) {
+        Ok(_) => Ok(NodeInfo {
+            protocol: Protocol::Legacy,
+            version: None,
+        }),
+        Err(err) if is_method_not_found(&err) => Err(Error::Rpc(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: "Unknown node, neither getBlockNumber nor blockNumber is supported".to_owned(),
+            data: None,
+        })),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns whether the node answered that it does not know the method.
+pub fn is_method_not_found(err: &Error) -> bool {
+    matches!(err, Error::Rpc(err) if err.code == METHOD_NOT_FOUND)
+}
@@ -0,0 +1,201 @@
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+
+use crate::transport::Transport;
+
+/// A request rate and a cap on concurrent requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    rate: Option<f64>,
+    burst: u32,
+    max_in_flight: Option<usize>,
+}
+
+impl Limit {
+    /// Allows `rate` requests per second on average.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not positive.
+    pub fn per_second(rate: f64) -> Limit {
+        assert!(rate > 0.0, "rate must be positive");
+        Limit {
+            rate: Some(rate),
+            burst: 1,
+            max_in_flight: None,
+        }
+    }
+
+    /// Does not limit the rate, e.g. to only cap concurrent requests.
+    pub fn unlimited() -> Limit {
+        Limit {
+            rate: None,
+            burst: 1,
+            max_in_flight: None,
+        }
+    }
+
+    /// Allows up to `burst` requests at once after a pause, 1 by default.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Makes further requests wait while `max` requests are unanswered.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max.max(1));
+        self
+    }
+}
+
+/// A token bucket and an in-flight counter enforcing a `Limit`.
+struct Gate {
+    limit: Limit,
+    bucket: Mutex<Bucket>,
+    in_flight: Mutex<usize>,
+    released: Condvar,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Gate {
+    fn new(limit: Limit) -> Gate {
+        Gate {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(limit.burst),
+                updated: Instant::now(),
+            }),
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a request may be sent, the request counts as in flight until the permit is
+    /// dropped.
+    fn enter(&self) -> Permit<'_> {
+        if let Some(max) = self.limit.max_in_flight {
+            let mut in_flight = lock(&self.in_flight);
+            while *in_flight >= max {
+                in_flight = self
+                    .released
+                    .wait(in_flight)
+                    .unwrap_or_else(|err| err.into_inner());
+            }
+            *in_flight += 1;
+        }
+
+        if let Some(rate) = self.limit.rate {
+            // Taking the token up front lets the bucket go into debt, so waiting requests are
+            // spaced out in the order they arrived.
+            let debt = {
+                let mut bucket = lock(&self.bucket);
+                let now = Instant::now();
+                let refill = now.duration_since(bucket.updated).as_secs_f64() * rate;
+                bucket.tokens = (bucket.tokens + refill).min(f64::from(self.limit.burst)) - 1.0;
+                bucket.updated = now;
+                -bucket.tokens
+            };
+            if debt > 0.0 {
+                thread::sleep(Duration::from_secs_f64(debt / rate));
+            }
+        }
+
+        Permit { gate: self }
+    }
+}
+
+struct Permit<'a> {
+    gate: &'a Gate,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.gate.limit.max_in_flight.is_some() {
+            *lock(&self.gate.in_flight) -= 1;
+            self.gate.released.notify_one();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+type Matcher = Box<dyn Fn(&Request) -> bool + Send + Sync>;
+
+/// Forwards requests to another transport no faster than the configured limits.
+///
+/// Every request is subject to the limit passed to `new`. Requests matching a class added with
+/// `class` are additionally subject to the limit of the first such class, so expensive calls can
+/// be throttled separately. Callers block until their request may be sent.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::ratelimit::{self, Limit, RateLimiter};
+/// let agent = jsonrpc::client::Client::new("http://seed-host.com:8648".to_owned(), None, None);
+/// let limiter = RateLimiter::new(agent, Limit::per_second(20.0).max_in_flight(8))
+///     .class(ratelimit::is_heavy, Limit::per_second(2.0).max_in_flight(1));
+/// let client = Client::new_with_transport(limiter);
+/// let result = client.get_block_by_number(882418, true);
+/// ```
+pub struct RateLimiter<T> {
+    inner: T,
+    global: Gate,
+    classes: Vec<(Matcher, Gate)>,
+}
+
+impl<T: Transport> RateLimiter<T> {
+    /// Limits all requests sent through `inner` to `limit`.
+    pub fn new(inner: T, limit: Limit) -> RateLimiter<T> {
+        RateLimiter {
+            inner,
+            global: Gate::new(limit),
+            classes: Vec::new(),
+        }
+    }
+
+    /// Limits the requests for which `matches` returns true to `limit` as well.
+    ///
+    /// Classes are tried in the order they were added.
+    pub fn class<F>(mut self, matches: F, limit: Limit) -> Self
+    where
+        F: Fn(&Request) -> bool + Send + Sync + 'static,
+    {
+        self.classes.push((Box::new(matches), Gate::new(limit)));
+        self
+    }
+}
+
+impl<T: Transport> Transport for RateLimiter<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        // The class is entered first, so a throttled request does not hold a global slot
+        // while it waits.
+        let _class = self
+            .classes
+            .iter()
+            .find(|(matches, _)| matches(request))
+            .map(|(_, gate)| gate.enter());
+        let _global = self.global.enter();
+        self.inner.send_request(request)
+    }
+}
+
+/// Returns whether the request is one of the expensive lookups, the transactions of an address
+/// or a block with its full transactions.
+pub fn is_heavy(request: &Request) -> bool {
+    match request.method {
+        "getTransactionsByAddress" => true,
+        "getBlockByNumber" | "getBlockByHash" => request.params.get(1) == Some(&true.into()),
+        _ => false,
+    }
+}
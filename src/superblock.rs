@@ -0,0 +1,72 @@
+use crate::block::{self, DecodeError, HASH_SIZE};
+use crate::primitives::Block;
+
+/// Depth of the largest target, 2^240, which has a difficulty of 1.
+const BLOCK_TARGET_MAX_DEPTH: i32 = 240;
+
+/// Returns the depth of a 256 bit big-endian target, how many times the largest target has to
+/// be halved to reach it, rounded down.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::superblock;
+/// let mut target = [0; 32];
+/// target[1] = 1;
+/// assert_eq!(superblock::target_depth(&target), 0);
+/// target[1] = 0;
+/// target[2] = 0x80;
+/// assert_eq!(superblock::target_depth(&target), 1);
+/// ```
+pub fn target_depth(target: &[u8; HASH_SIZE]) -> i32 {
+    BLOCK_TARGET_MAX_DEPTH - log2_ceil(target)
+}
+
+/// Returns the depth of a proof-of-work hash, the depth of the smallest target it meets.
+pub fn hash_depth(pow: &[u8; HASH_SIZE]) -> i32 {
+    target_depth(pow)
+}
+
+/// Returns the depth of the target of a difficulty.
+pub fn difficulty_depth(difficulty: f64) -> i32 {
+    difficulty.log2().floor() as i32
+}
+
+/// Returns the superblock level of a block, how much deeper its proof-of-work hash is than its
+/// target.
+///
+/// A block of level `n` counts as a block at the `n` lowest levels of the interlink.
+pub fn level(pow: &[u8; HASH_SIZE], target: &[u8; HASH_SIZE]) -> u32 {
+    (hash_depth(pow) - target_depth(target)).max(0) as u32
+}
+
+/// Returns the superblock level of a block returned by the node, from its `pow` and
+/// `difficulty`.
+pub fn block_level(block: &Block) -> Result<u32, DecodeError> {
+    let pow = block::hash(&block.pow, "pow")?;
+    let difficulty: f64 = block
+        .difficulty
+        .parse()
+        .map_err(|_| DecodeError::InvalidField("difficulty"))?;
+    if !(difficulty >= 1.0 && difficulty.is_finite()) {
+        return Err(DecodeError::InvalidField("difficulty"));
+    }
+    Ok((hash_depth(&pow) - difficulty_depth(difficulty)).max(0) as u32)
+}
+
+/// Returns the base 2 logarithm of a big-endian number, rounded up.
+fn log2_ceil(value: &[u8; HASH_SIZE]) -> i32 {
+    let first = match value.iter().position(|byte| *byte != 0) {
+        Some(first) => first,
+        // The logarithm of zero is minus infinity, treat it as the deepest possible hash.
+        None => return -(BLOCK_TARGET_MAX_DEPTH + 1),
+    };
+    let bits = (HASH_SIZE - first) as i32 * 8 - value[first].leading_zeros() as i32;
+    let power_of_two =
+        value[first].is_power_of_two() && value[first + 1..].iter().all(|byte| *byte == 0);
+    if power_of_two {
+        bits - 1
+    } else {
+        bits
+    }
+}
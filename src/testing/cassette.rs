@@ -0,0 +1,177 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use jsonrpc::error::{Error, RpcError};
+use jsonrpc::{Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transport::Transport;
+
+/// A request and the response the node gave to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub params: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// Forwards requests to another transport and writes every exchange to a cassette file.
+///
+/// The cassette is a JSON array of interactions that is rewritten after every request, so it
+/// is complete even if the program does not shut down cleanly. Requests that fail before the
+/// node answered are not recorded.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::testing::RecordingTransport;
+/// let agent = jsonrpc::client::Client::new("http://seed-host.com:8648".to_owned(), None, None);
+/// let client = Client::new_with_transport(RecordingTransport::new(agent, "node.json"));
+/// let result = client.block_number();
+/// ```
+pub struct RecordingTransport<T> {
+    inner: T,
+    path: PathBuf,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Records the exchanges of `inner` into the cassette at `path`.
+    pub fn new<P: AsRef<Path>>(inner: T, path: P) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner,
+            path: path.as_ref().to_owned(),
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the interactions recorded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let response = self.inner.send_request(request)?;
+
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(Interaction {
+            method: request.method.to_owned(),
+            params: request.params.to_vec(),
+            result: response.result.clone(),
+            error: response.error.clone(),
+        });
+
+        let cassette = serde_json::to_vec_pretty(&*interactions)?;
+        if let Err(err) = fs::write(&self.path, cassette) {
+            return Err(Error::Hyper(err.into()));
+        }
+
+        Ok(response)
+    }
+}
+
+/// Answers requests from a cassette written by [`RecordingTransport`].
+///
+/// Requests are matched on method and parameters, each interaction is used once in the order
+/// it was recorded. When no unused interaction matches, the last matching one is repeated. In
+/// strict mode only unused interactions are answered.
+///
+/// Unanswered requests fail with an I/O error of kind `NotFound`, as if the node could not be
+/// reached, so they are not mistaken for an answer of the node.
+pub struct ReplayTransport {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+    strict: bool,
+}
+
+impl ReplayTransport {
+    /// Loads the cassette at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<ReplayTransport> {
+        let interactions = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(ReplayTransport::new(interactions))
+    }
+
+    /// Replays the given interactions.
+    pub fn new(interactions: Vec<Interaction>) -> ReplayTransport {
+        ReplayTransport {
+            used: Mutex::new(vec![false; interactions.len()]),
+            interactions,
+            strict: false,
+        }
+    }
+
+    /// Enables or disables strict matching.
+    pub fn strict(mut self, strict: bool) -> ReplayTransport {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns the interactions that were not replayed yet.
+    pub fn remaining(&self) -> Vec<Interaction> {
+        let used = self.used.lock().unwrap();
+        self.interactions
+            .iter()
+            .zip(used.iter())
+            .filter(|(_, used)| !**used)
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    fn find(&self, request: &Request) -> Option<usize> {
+        let mut used = self.used.lock().unwrap();
+        let matches = |interaction: &Interaction| {
+            interaction.method == request.method && interaction.params == request.params
+        };
+
+        let unused = self
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| !used[i] && matches(interaction));
+        if let Some(index) = unused {
+            used[index] = true;
+            return Some(index);
+        }
+        if self.strict {
+            return None;
+        }
+
+        self.interactions.iter().rposition(matches)
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        match self.find(request) {
+            Some(index) => {
+                let interaction = &self.interactions[index];
+                Ok(Response {
+                    result: interaction.result.clone(),
+                    error: interaction.error.clone(),
+                    id: request.id.clone(),
+                    jsonrpc: Some("2.0".to_owned()),
+                })
+            }
+            None => Err(Error::Hyper(
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "no recorded interaction for {} {}",
+                        request.method,
+                        Value::from(request.params.to_vec())
+                    ),
+                )
+                .into(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,591 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use jsonrpc::error::{Error, RpcError};
+use jsonrpc::{Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::address;
+use crate::fee;
+use crate::primitives::{Account, Block, Transaction, TransactionReceipt, TransactionSequence};
+use crate::transport::Transport;
+use crate::Client;
+
+const GENESIS_TIMESTAMP: u64 = 1_523_727_000;
+const BLOCK_TIME: u64 = 60;
+
+/// A stateful in-memory node for end-to-end tests.
+///
+/// The chain keeps account balances, accepts transactions through `sendTransaction` and
+/// `sendRawTransaction` into its mempool and only produces blocks when asked to. Queries are
+/// answered from the current state, so balances, receipts, blocks and the transaction history
+/// of an address stay consistent with each other, also across forced reorgs.
+///
+/// Raw transactions created by `createRawTransaction` use a simulation specific encoding and
+/// are only understood by the simulated chain itself.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::primitives::OutgoingTransaction;
+/// use nimiq_rpc::testing::SimulatedChain;
+/// let chain = SimulatedChain::new();
+/// chain.set_balance("NQ32 R6DB VFM5 M931 7X4E 0N5Q LJ56 9QCR 4T42", 1000);
+/// let client = chain.client();
+/// let hash = client
+///     .send_transaction(&OutgoingTransaction {
+///         from: "NQ32 R6DB VFM5 M931 7X4E 0N5Q LJ56 9QCR 4T42",
+///         to: "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2",
+///         value: 100,
+///         fee: 0,
+///     })
+///     .unwrap();
+/// chain.mine_block();
+/// assert_eq!(client.get_transaction_receipt(&hash).unwrap().confirmations, 1);
+/// assert_eq!(client.get_balance("NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2").unwrap(), 100);
+/// ```
+#[derive(Clone)]
+pub struct SimulatedChain {
+    state: Arc<Mutex<State>>,
+}
+
+/// An owned counterpart of `OutgoingTransaction`, also used as raw transaction encoding.
+#[derive(Serialize, Deserialize)]
+struct Outgoing {
+    from: String,
+    to: String,
+    value: u64,
+    fee: u32,
+}
+
+struct State {
+    balances: HashMap<String, u64>,
+    blocks: Vec<Block>,
+    mempool: Vec<Transaction>,
+    miner: String,
+    min_fee_per_byte: u32,
+    nonce: u64,
+}
+
+impl SimulatedChain {
+    /// Creates a chain that only contains a genesis block at height 1.
+    pub fn new() -> SimulatedChain {
+        let mut state = State {
+            balances: HashMap::new(),
+            blocks: Vec::new(),
+            mempool: Vec::new(),
+            miner: "0000000000000000000000000000000000000000".to_owned(),
+            min_fee_per_byte: 0,
+            nonce: 0,
+        };
+        state.push_block(Vec::new());
+
+        SimulatedChain {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Returns a client connected to this chain.
+    pub fn client(&self) -> Client {
+        Client::new_with_transport(self.clone())
+    }
+
+    /// Sets the balance of an address, in Luna.
+    pub fn set_balance(&self, address: &str, balance: u64) {
+        let address = to_hex(address).expect("invalid address");
+        self.lock().balances.insert(address, balance);
+    }
+
+    /// Returns the balance of an address, in Luna.
+    pub fn balance(&self, address: &str) -> u64 {
+        to_hex(address)
+            .and_then(|address| self.lock().balances.get(&address).cloned())
+            .unwrap_or(0)
+    }
+
+    /// Sets the address that receives the fees of mined blocks.
+    pub fn set_miner(&self, address: &str) {
+        self.lock().miner = to_hex(address).expect("invalid address");
+    }
+
+    /// Sets the minimum fee per byte a transaction needs to be accepted into the mempool.
+    pub fn set_min_fee_per_byte(&self, fee: u32) {
+        self.lock().min_fee_per_byte = fee;
+    }
+
+    /// Returns the height of the head block.
+    pub fn height(&self) -> u64 {
+        self.lock().height()
+    }
+
+    /// Returns the transactions waiting in the mempool.
+    pub fn mempool(&self) -> Vec<Transaction> {
+        self.lock().mempool.clone()
+    }
+
+    /// Mines a block including all mempool transactions the senders can afford.
+    ///
+    /// Transactions that cannot be paid for stay in the mempool.
+    pub fn mine_block(&self) -> Block {
+        let mut state = self.lock();
+        let transactions = std::mem::take(&mut state.mempool);
+        state.push_block(transactions)
+    }
+
+    /// Mines a block without transactions, the mempool stays untouched.
+    pub fn mine_empty_block(&self) -> Block {
+        self.lock().push_block(Vec::new())
+    }
+
+    /// Removes the given number of blocks from the head of the chain.
+    ///
+    /// Balances are rolled back and the transactions of the removed blocks are returned to the
+    /// mempool. The genesis block is never removed.
+    pub fn revert(&self, depth: u64) -> Vec<Block> {
+        let mut state = self.lock();
+        let mut reverted = Vec::new();
+        for _ in 0..depth {
+            match state.pop_block() {
+                Some(block) => reverted.push(block),
+                None => break,
+            }
+        }
+        reverted
+    }
+
+    /// Replaces the given number of blocks by a longer fork of empty blocks.
+    ///
+    /// The transactions of the replaced blocks are returned to the mempool.
+    pub fn reorg(&self, depth: u64) -> Vec<Block> {
+        let reverted = self.revert(depth);
+        for _ in 0..=reverted.len() {
+            self.mine_empty_block();
+        }
+        reverted
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Default for SimulatedChain {
+    fn default() -> SimulatedChain {
+        SimulatedChain::new()
+    }
+}
+
+impl Transport for SimulatedChain {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let (result, error) = match self.lock().handle(request.method, request.params) {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Ok(Response {
+            result,
+            error,
+            id: request.id.clone(),
+            jsonrpc: Some("2.0".to_owned()),
+        })
+    }
+}
+
+impl State {
+    fn height(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    fn next_hash(&mut self) -> String {
+        self.nonce += 1;
+        let mut seed = self.nonce;
+        (0..4)
+            .map(|_| {
+                // splitmix64
+                seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = seed;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                format!("{:016x}", z ^ (z >> 31))
+            })
+            .collect()
+    }
+
+    fn push_block(&mut self, candidates: Vec<Transaction>) -> Block {
+        let number = self.height() + 1;
+        let hash = self.next_hash();
+        let parent_hash = self
+            .blocks
+            .last()
+            .map(|block| block.hash.clone())
+            .unwrap_or_else(|| "0".repeat(64));
+        let timestamp = GENESIS_TIMESTAMP + number * BLOCK_TIME;
+        let miner = self.miner.clone();
+
+        let mut transactions = Vec::new();
+        for mut tx in candidates {
+            let balance = self.balances.get(&tx.from).cloned().unwrap_or(0);
+            if balance < tx.value + tx.fee {
+                self.mempool.push(tx);
+                continue;
+            }
+            self.balances
+                .insert(tx.from.clone(), balance - tx.value - tx.fee);
+            *self.balances.entry(tx.to.clone()).or_insert(0) += tx.value;
+            *self.balances.entry(miner.clone()).or_insert(0) += tx.fee;
+
+            tx.block_hash = hash.clone();
+            tx.block_number = number;
+            tx.timestamp = timestamp;
+            tx.transaction_index = Some(transactions.len() as i64);
+            transactions.push(tx);
+        }
+
+        let block = Block {
+            number,
+            hash: hash.clone(),
+            pow: self.next_hash(),
+            parent_hash,
+            nonce: number,
+            body_hash: self.next_hash(),
+            accounts_hash: self.next_hash(),
+            miner: miner.clone(),
+            miner_address: address::to_user_friendly(&miner).unwrap(),
+            difficulty: "1".to_owned(),
+            extra_data: String::new(),
+            size: (146
+                + transactions
+                    .iter()
+                    .map(fee::transaction_size)
+                    .sum::<usize>()) as u32,
+            timestamp,
+            transactions: TransactionSequence::Transactions(transactions),
+        };
+        self.blocks.push(block.clone());
+        block
+    }
+
+    fn pop_block(&mut self) -> Option<Block> {
+        if self.blocks.len() <= 1 {
+            return None;
+        }
+        let block = self.blocks.pop()?;
+
+        let transactions = transactions(&block).to_vec();
+        for tx in transactions.iter().rev() {
+            // Balances may have been changed with `set_balance` since the block was mined.
+            let miner = self.balances.entry(block.miner.clone()).or_insert(0);
+            *miner = miner.saturating_sub(tx.fee);
+            let to = self.balances.entry(tx.to.clone()).or_insert(0);
+            *to = to.saturating_sub(tx.value);
+            let from = self.balances.entry(tx.from.clone()).or_insert(0);
+            *from = from.saturating_add(tx.value + tx.fee);
+        }
+        let mut pending: Vec<Transaction> = transactions.into_iter().map(pending).collect();
+        pending.append(&mut self.mempool);
+        self.mempool = pending;
+
+        Some(block)
+    }
+
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let head = self.height();
+        match method {
+            "blockNumber" => Ok(json!(head)),
+            "consensus" => Ok(json!("established")),
+            "syncing" => Ok(json!(false)),
+            "minFeePerByte" => Ok(json!(self.min_fee_per_byte)),
+            "getBalance" => {
+                let address = address_param(params, 0)?;
+                Ok(json!(self.balances.get(&address).cloned().unwrap_or(0)))
+            }
+            "getAccount" => {
+                let address = address_param(params, 0)?;
+                to_value(Account {
+                    address: address::to_user_friendly(&address).unwrap(),
+                    balance: self.balances.get(&address).cloned().unwrap_or(0),
+                    id: address,
+                    r#type: 0,
+                })
+            }
+            "getBlockByNumber" => {
+                let number = u64_param(params, 0)?;
+                let block = self.blocks.iter().find(|block| block.number == number);
+                Ok(render_block(block, bool_param(params, 1), head))
+            }
+            "getBlockByHash" => {
+                let hash = str_param(params, 0)?.to_lowercase();
+                let block = self.blocks.iter().find(|block| block.hash == hash);
+                Ok(render_block(block, bool_param(params, 1), head))
+            }
+            "getBlockTransactionCountByNumber" => {
+                let number = u64_param(params, 0)?;
+                let block = self.blocks.iter().find(|block| block.number == number);
+                Ok(block.map_or(Value::Null, |block| json!(transactions(block).len())))
+            }
+            "getBlockTransactionCountByHash" => {
+                let hash = str_param(params, 0)?.to_lowercase();
+                let block = self.blocks.iter().find(|block| block.hash == hash);
+                Ok(block.map_or(Value::Null, |block| json!(transactions(block).len())))
+            }
+            "getTransactionByBlockNumberAndIndex" => {
+                let number = u64_param(params, 0)?;
+                let index = u64_param(params, 1)? as usize;
+                let tx = self
+                    .blocks
+                    .iter()
+                    .find(|block| block.number == number)
+                    .and_then(|block| transactions(block).get(index));
+                Ok(render_transaction(tx, head))
+            }
+            "getTransactionByBlockHashAndIndex" => {
+                let hash = str_param(params, 0)?.to_lowercase();
+                let index = u64_param(params, 1)? as usize;
+                let tx = self
+                    .blocks
+                    .iter()
+                    .find(|block| block.hash == hash)
+                    .and_then(|block| transactions(block).get(index));
+                Ok(render_transaction(tx, head))
+            }
+            "getTransactionByHash" => {
+                let hash = str_param(params, 0)?.to_lowercase();
+                let tx = self
+                    .included()
+                    .find(|tx| tx.hash == hash)
+                    .or_else(|| self.mempool.iter().find(|tx| tx.hash == hash));
+                Ok(render_transaction(tx, head))
+            }
+            "getTransactionReceipt" => {
+                let hash = str_param(params, 0)?.to_lowercase();
+                match self.included().find(|tx| tx.hash == hash) {
+                    Some(tx) => to_value(TransactionReceipt {
+                        transaction_hash: tx.hash.clone(),
+                        transaction_index: tx.transaction_index.unwrap_or(0),
+                        block_number: tx.block_number,
+                        block_hash: tx.block_hash.clone(),
+                        confirmations: head - tx.block_number + 1,
+                        timestamp: tx.timestamp,
+                    }),
+                    None => Ok(Value::Null),
+                }
+            }
+            "getTransactionsByAddress" => {
+                let address = address_param(params, 0)?;
+                let amount = params.get(1).and_then(Value::as_u64).unwrap_or(1000) as usize;
+                let transactions: Vec<Value> = self
+                    .included()
+                    .filter(|tx| tx.from == address || tx.to == address)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                    .take(amount)
+                    .map(|tx| render_transaction(Some(tx), head))
+                    .collect();
+                Ok(json!(transactions))
+            }
+            "mempoolContent" => {
+                if bool_param(params, 0) {
+                    to_value(&self.mempool)
+                } else {
+                    Ok(json!(self
+                        .mempool
+                        .iter()
+                        .map(|tx| tx.hash.clone())
+                        .collect::<Vec<_>>()))
+                }
+            }
+            "mempool" => {
+                let mut buckets: Vec<u32> = Vec::new();
+                let mut counts: HashMap<u32, u64> = HashMap::new();
+                for tx in &self.mempool {
+                    let bucket = fee::fee_per_byte(tx) as u32;
+                    if !buckets.contains(&bucket) {
+                        buckets.push(bucket);
+                    }
+                    *counts.entry(bucket).or_insert(0) += 1;
+                }
+                buckets.sort_unstable_by(|a, b| b.cmp(a));
+
+                let mut mempool = json!({"total": self.mempool.len(), "buckets": buckets});
+                for (bucket, count) in counts {
+                    mempool[bucket.to_string()] = json!(count);
+                }
+                Ok(mempool)
+            }
+            "createRawTransaction" => {
+                let tx: Outgoing = outgoing_param(params)?;
+                Ok(json!(hex::encode(serde_json::to_vec(&tx).unwrap())))
+            }
+            "sendRawTransaction" => {
+                let raw = hex::decode(str_param(params, 0)?).map_err(|_| invalid_params())?;
+                let tx = serde_json::from_slice(&raw).map_err(|_| invalid_params())?;
+                self.accept(tx)
+            }
+            "sendTransaction" => {
+                let tx = outgoing_param(params)?;
+                self.accept(tx)
+            }
+            _ => Err(RpcError {
+                code: -32601,
+                message: "Method not found".to_owned(),
+                data: None,
+            }),
+        }
+    }
+
+    fn accept(&mut self, tx: Outgoing) -> Result<Value, RpcError> {
+        let from = to_hex(&tx.from).ok_or_else(invalid_params)?;
+        let to = to_hex(&tx.to).ok_or_else(invalid_params)?;
+
+        let needed = self
+            .mempool
+            .iter()
+            .filter(|pending| pending.from == from)
+            .fold(
+                tx.value.checked_add(u64::from(tx.fee)),
+                |needed, pending| needed?.checked_add(pending.value + pending.fee),
+            );
+        let balance = self.balances.get(&from).cloned().unwrap_or(0);
+        if needed.is_none_or(|needed| balance < needed) {
+            return Err(RpcError {
+                code: -32603,
+                message: "Insufficient funds".to_owned(),
+                data: None,
+            });
+        }
+
+        let transaction = Transaction {
+            hash: self.next_hash(),
+            block_hash: String::new(),
+            block_number: 0,
+            timestamp: 0,
+            confirmations: 0,
+            transaction_index: None,
+            from_address: address::to_user_friendly(&from).unwrap(),
+            from,
+            to_address: address::to_user_friendly(&to).unwrap(),
+            to,
+            value: tx.value,
+            fee: u64::from(tx.fee),
+            data: None,
+            flags: 0,
+        };
+        if fee::fee_per_byte(&transaction) < f64::from(self.min_fee_per_byte) {
+            return Err(RpcError {
+                code: -32603,
+                message: "Fee too low".to_owned(),
+                data: None,
+            });
+        }
+
+        let hash = transaction.hash.clone();
+        self.mempool.push(transaction);
+        Ok(json!(hash))
+    }
+
+    fn included(&self) -> impl DoubleEndedIterator<Item = &Transaction> {
+        self.blocks
+            .iter()
+            .flat_map(|block| transactions(block).iter())
+    }
+}
+
+fn transactions(block: &Block) -> &[Transaction] {
+    match &block.transactions {
+        TransactionSequence::Transactions(transactions) => transactions,
+        TransactionSequence::BlockHashes(_) => &[],
+    }
+}
+
+fn pending(mut tx: Transaction) -> Transaction {
+    tx.block_hash = String::new();
+    tx.block_number = 0;
+    tx.timestamp = 0;
+    tx.transaction_index = None;
+    tx
+}
+
+fn render_block(block: Option<&Block>, full_transactions: bool, head: u64) -> Value {
+    let block = match block {
+        Some(block) => block,
+        None => return Value::Null,
+    };
+
+    let mut block = block.clone();
+    let transactions = transactions(&block).to_vec();
+    block.transactions = if full_transactions {
+        TransactionSequence::Transactions(
+            transactions
+                .into_iter()
+                .map(|mut tx| {
+                    tx.confirmations = head - tx.block_number + 1;
+                    tx
+                })
+                .collect(),
+        )
+    } else {
+        TransactionSequence::BlockHashes(transactions.into_iter().map(|tx| tx.hash).collect())
+    };
+    serde_json::to_value(block).unwrap()
+}
+
+fn render_transaction(tx: Option<&Transaction>, head: u64) -> Value {
+    match tx {
+        Some(tx) => {
+            let mut tx = tx.clone();
+            if tx.block_number > 0 {
+                tx.confirmations = head - tx.block_number + 1;
+            }
+            serde_json::to_value(tx).unwrap()
+        }
+        None => Value::Null,
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|_| invalid_params())
+}
+
+fn to_hex(address: &str) -> Option<String> {
+    address::to_hex(address)
+        .or_else(|| address::to_user_friendly(address).map(|_| address.to_lowercase()))
+}
+
+fn invalid_params() -> RpcError {
+    RpcError {
+        code: -32602,
+        message: "Invalid params".to_owned(),
+        data: None,
+    }
+}
+
+fn str_param(params: &[Value], index: usize) -> Result<&str, RpcError> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(invalid_params)
+}
+
+fn u64_param(params: &[Value], index: usize) -> Result<u64, RpcError> {
+    params
+        .get(index)
+        .and_then(Value::as_u64)
+        .ok_or_else(invalid_params)
+}
+
+fn bool_param(params: &[Value], index: usize) -> bool {
+    params.get(index).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn address_param(params: &[Value], index: usize) -> Result<String, RpcError> {
+    to_hex(str_param(params, index)?).ok_or_else(invalid_params)
+}
+
+fn outgoing_param(params: &[Value]) -> Result<Outgoing, RpcError> {
+    params
+        .first()
+        .cloned()
+        .and_then(|tx| serde_json::from_value(tx).ok())
+        .ok_or_else(invalid_params)
+}
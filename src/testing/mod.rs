@@ -0,0 +1,7 @@
+mod cassette;
+mod chain;
+mod server;
+
+pub use self::cassette::{Interaction, RecordingTransport, ReplayTransport};
+pub use self::chain::SimulatedChain;
+pub use self::server::{MockServer, RecordedRequest};
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use jsonrpc::error::RpcError;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::Client;
+
+type Handler = Box<dyn Fn(&[Value]) -> Reply + Send>;
+
+enum Reply {
+    Result(Value),
+    Error(RpcError),
+    Disconnect,
+}
+
+/// A JSON-RPC request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub params: Vec<Value>,
+}
+
+#[derive(Default)]
+struct State {
+    handlers: HashMap<String, Handler>,
+    latencies: HashMap<String, Duration>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// An in-process HTTP JSON-RPC server that answers with scripted responses.
+///
+/// Every method has to be scripted with one of the `on_*` methods, requests for other methods
+/// are answered with a "Method not found" error. All received requests are recorded and can
+/// be inspected with `requests`.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::testing::MockServer;
+/// let server = MockServer::start();
+/// server.on("blockNumber", 882418);
+/// let client = server.client();
+/// assert_eq!(client.block_number().unwrap(), 882418);
+/// assert_eq!(server.requests()[0].method, "blockNumber");
+/// ```
+pub struct MockServer {
+    address: String,
+    state: Arc<Mutex<State>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free port of the loopback interface.
+    pub fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let address = listener.local_addr().unwrap().to_string();
+        let state = Arc::new(Mutex::new(State::default()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let state = Arc::clone(&state);
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let state = Arc::clone(&state);
+                        thread::spawn(move || handle_connection(stream, &state));
+                    }
+                }
+            })
+        };
+
+        MockServer {
+            address,
+            state,
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the URL the server is listening on.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// Returns a client connected to this server.
+    pub fn client(&self) -> Client {
+        Client::new(&self.url())
+    }
+
+    /// Answers every call of `method` with the given result.
+    pub fn on<T: Serialize>(&self, method: &str, result: T) -> &MockServer {
+        let result = serde_json::to_value(result).expect("result is not serializable");
+        self.handle(method, move |_| Reply::Result(result.clone()))
+    }
+
+    /// Answers calls of `method` with the result of a closure that receives the parameters.
+    pub fn on_call<F>(&self, method: &str, f: F) -> &MockServer
+    where
+        F: Fn(&[Value]) -> Result<Value, RpcError> + Send + 'static,
+    {
+        self.handle(method, move |params| match f(params) {
+            Ok(result) => Reply::Result(result),
+            Err(err) => Reply::Error(err),
+        })
+    }
+
+    /// Answers every call of `method` with a JSON-RPC error.
+    pub fn on_error(&self, method: &str, code: i32, message: &str) -> &MockServer {
+        let error = RpcError {
+            code,
+            message: message.to_owned(),
+            data: None,
+        };
+        self.handle(method, move |_| Reply::Error(error.clone()))
+    }
+
+    /// Closes the connection without answering when `method` is called.
+    pub fn on_disconnect(&self, method: &str) -> &MockServer {
+        self.handle(method, |_| Reply::Disconnect)
+    }
+
+    /// Delays every answer to `method` by the given duration.
+    pub fn latency(&self, method: &str, latency: Duration) -> &MockServer {
+        self.lock().latencies.insert(method.to_owned(), latency);
+        self
+    }
+
+    /// Returns all requests received so far, in the order they arrived.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Returns all requests received so far for the given method.
+    pub fn requests_for(&self, method: &str) -> Vec<RecordedRequest> {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|request| request.method == method)
+            .cloned()
+            .collect()
+    }
+
+    fn handle<F>(&self, method: &str, f: F) -> &MockServer
+    where
+        F: Fn(&[Value]) -> Reply + Send + 'static,
+    {
+        self.lock().handlers.insert(method.to_owned(), Box::new(f));
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // Wake up the accepting thread so it notices the shutdown.
+        let _ = TcpStream::connect(&self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) {
+    let body = match read_request(&stream) {
+        Some(body) => body,
+        None => return,
+    };
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Ok(Value::Array(requests)) => {
+            let mut responses = Vec::new();
+            for request in requests {
+                match dispatch(&request, state) {
+                    Some(response) => responses.push(response),
+                    None => return disconnect(stream),
+                }
+            }
+            Value::Array(responses)
+        }
+        Ok(request) => match dispatch(&request, state) {
+            Some(response) => response,
+            None => return disconnect(stream),
+        },
+        Err(_) => json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32700, "message": "Parse error"},
+        }),
+    };
+
+    write_response(stream, &response.to_string());
+}
+
+fn dispatch(request: &Value, state: &Mutex<State>) -> Option<Value> {
+    let method = request["method"].as_str().unwrap_or_default().to_owned();
+    let params = match &request["params"] {
+        Value::Array(params) => params.clone(),
+        Value::Null => Vec::new(),
+        params => vec![params.clone()],
+    };
+    let id = request["id"].clone();
+
+    let (reply, latency) = {
+        let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+        state.requests.push(RecordedRequest {
+            method: method.clone(),
+            params: params.clone(),
+        });
+        let reply = match state.handlers.get(&method) {
+            Some(handler) => handler(&params),
+            None => Reply::Error(RpcError {
+                code: -32601,
+                message: "Method not found".to_owned(),
+                data: None,
+            }),
+        };
+        (reply, state.latencies.get(&method).cloned())
+    };
+
+    if let Some(latency) = latency {
+        thread::sleep(latency);
+    }
+
+    match reply {
+        Reply::Result(result) => Some(json!({"jsonrpc": "2.0", "id": id, "result": result})),
+        Reply::Error(error) => Some(json!({"jsonrpc": "2.0", "id": id, "error": error})),
+        Reply::Disconnect => None,
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Vec<u8>> {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
+
+fn write_response(mut stream: TcpStream, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let _ = stream.flush();
+}
+
+fn disconnect(stream: TcpStream) {
+    let _ = stream.shutdown(Shutdown::Both);
+}
@@ -0,0 +1,101 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use jsonrpc::client::Client as RpcClient;
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+
+/// Delivers JSON-RPC requests to a node and returns its responses.
+///
+/// `Client` uses HTTP through `jsonrpc::client::Client` by default, other transports can be
+/// passed to `Client::new_with_transport`.
+pub trait Transport: Send + Sync {
+    fn send_request(&self, request: &Request) -> Result<Response, Error>;
+}
+
+impl Transport for RpcClient {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        RpcClient::send_request(self, request)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        (**self).send_request(request)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        (**self).send_request(request)
+    }
+}
+
+/// An error of this crate that `Error` carries as `Hyper`, as it has no variant of its own for
+/// it, e.g. a [`DeepReorg`](crate::follower::DeepReorg).
+struct TypedError(Box<dyn error::Error + Send + Sync>);
+
+impl fmt::Debug for TypedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for TypedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl error::Error for TypedError {}
+
+/// Wraps an error of this crate into an `Error`, [`downcast_error`] returns it again.
+pub(crate) fn typed_error<E>(kind: io::ErrorKind, err: E) -> Error
+where
+    E: error::Error + Send + Sync + 'static,
+{
+    Error::Hyper(io::Error::new(kind, TypedError(Box::new(err))).into())
+}
+
+/// Returns the error of this crate of type `E` wrapped in `err`, if it is one.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::follower::DeepReorg;
+/// use nimiq_rpc::transport;
+/// # let client = nimiq_rpc::Client::new("http://seed-host.com:8648");
+/// # let mut follower = nimiq_rpc::follower::ChainFollower::new(&client, 10);
+/// if let Err(err) = follower.poll() {
+///     if let Some(reorg) = transport::downcast_error::<DeepReorg>(&err) {
+///         println!("finalized block {} was reverted", reorg.number);
+///     }
+/// }
+/// ```
+pub fn downcast_error<E: error::Error + 'static>(err: &Error) -> Option<&E> {
+    match err {
+        Error::Hyper(hyper::Error::Io(err)) => err
+            .get_ref()?
+            .downcast_ref::<TypedError>()?
+            .0
+            .downcast_ref(),
+        _ => None,
+    }
+}
+
+/// Returns whether `err` is a failure to reach the node or to read its answer.
+///
+/// Error responses of the node and errors of this crate, such as a
+/// [`DeepReorg`](crate::follower::DeepReorg), are not, even though `Error` carries the latter
+/// as `Hyper`.
+pub fn is_transport_error(err: &Error) -> bool {
+    match err {
+        Error::Hyper(hyper::Error::Io(err)) => {
+            err.get_ref().is_none_or(|inner| !inner.is::<TypedError>())
+        }
+        Error::Hyper(_) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use hyper::client::Client as HyperClient;
+use hyper::header::{Authorization, Basic, ContentType, Headers};
+use hyper::net::{NetworkConnector, NetworkStream};
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+
+use crate::transport::Transport;
+
+/// The URL requests are posted to, the host is only used for the `Host` header.
+const URL: &str = "http://localhost/";
+
+/// Sends the HTTP JSON-RPC requests of `Client` over a Unix domain socket instead of TCP.
+///
+/// Requests are the same as those of `jsonrpc::client::Client`, a `POST` with a JSON body and
+/// optional basic authentication, so any node that serves its RPC on a socket understands them.
+///
+/// # Example
+///
+/// ```
+/// use nimiq_rpc::Client;
+/// use nimiq_rpc::unix::UnixSocketTransport;
+/// let transport = UnixSocketTransport::new("/var/run/nimiq/rpc.sock")
+///     .timeout(std::time::Duration::from_secs(10));
+/// let client = Client::new_with_transport(transport);
+/// let result = client.block_number();
+/// ```
+pub struct UnixSocketTransport {
+    client: HyperClient,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl UnixSocketTransport {
+    /// Connects to the socket at `path` for every request.
+    pub fn new<P: AsRef<Path>>(path: P) -> UnixSocketTransport {
+        UnixSocketTransport {
+            client: HyperClient::with_connector(UnixConnector {
+                path: path.as_ref().to_owned(),
+            }),
+            username: None,
+            password: None,
+        }
+    }
+
+    /// Authenticates every request with the given credentials.
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.username = Some(username.to_owned());
+        self.password = Some(password.to_owned());
+        self
+    }
+
+    /// Sets how long to wait for the node to accept a request and to answer it, by default
+    /// without a limit.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_read_timeout(Some(timeout));
+        self.client.set_write_timeout(Some(timeout));
+        self
+    }
+}
+
+impl Transport for UnixSocketTransport {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let body = serde_json::to_vec(request)?;
+
+        let mut headers = Headers::new();
+        headers.set(ContentType::json());
+        if let Some(username) = &self.username {
+            headers.set(Authorization(Basic {
+                username: username.clone(),
+                password: self.password.clone(),
+            }));
+        }
+
+        let response = self
+            .client
+            .post(URL)
+            .headers(headers)
+            .body(&body[..])
+            .send()
+            .map_err(Error::Hyper)?;
+        Ok(serde_json::from_reader(response)?)
+    }
+}
+
+struct UnixConnector {
+    path: PathBuf,
+}
+
+impl NetworkConnector for UnixConnector {
+    type Stream = UnixSocketStream;
+
+    fn connect(&self, _host: &str, _port: u16, _scheme: &str) -> hyper::Result<UnixSocketStream> {
+        Ok(UnixSocketStream(UnixStream::connect(&self.path)?))
+    }
+}
+
+/// A Unix socket that hyper can send HTTP over.
+struct UnixSocketStream(UnixStream);
+
+impl Read for UnixSocketStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for UnixSocketStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl NetworkStream for UnixSocketStream {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "Unix sockets have no IP address",
+        ))
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.set_write_timeout(timeout)
+    }
+
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.0.shutdown(how)
+    }
+}
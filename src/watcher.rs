@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::thread;
+use std::time::Duration;
+
+use jsonrpc::error::Error;
+
+use crate::follower::{ChainEvent, ChainFollower};
+use crate::primitives::{Block, Transaction, TransactionSequence};
+use crate::Client;
+
+/// Whether a transaction pays into or out of a watched address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+/// State of a watched transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    /// The transaction is waiting in the mempool.
+    Pending,
+    /// The transaction is included in a block with the given number of confirmations.
+    Confirmed(u64),
+    /// The transaction reached the confirmation depth and will not be reported again.
+    Finalized,
+    /// The block including the transaction was reverted.
+    Reverted,
+}
+
+/// A payment to or from one of the watched addresses.
+#[derive(Debug, Clone)]
+pub struct PaymentEvent {
+    pub address: String,
+    pub direction: Direction,
+    pub status: PaymentStatus,
+    pub transaction: Transaction,
+}
+
+/// Watches a set of addresses for incoming and outgoing transactions.
+///
+/// The watcher is built on a [`ChainFollower`] requesting full transactions. Transactions of
+/// watched addresses are reported when they enter the mempool, every time a new block adds a
+/// confirmation, when they reach the confirmation depth of the follower and when their block
+/// is reverted. A reorg below the last finalized block fails the poll with a
+/// [`DeepReorg`](crate::follower::DeepReorg) error, as its payments were already reported as
+/// final.
+pub struct AddressWatcher<'a> {
+    client: &'a Client,
+    follower: ChainFollower<'a>,
+    addresses: HashSet<String>,
+    include_mempool: bool,
+    poll_interval: Duration,
+    pending: HashSet<String>,
+    included: HashMap<String, Transaction>,
+    events: VecDeque<PaymentEvent>,
+}
+
+impl<'a> AddressWatcher<'a> {
+    /// Creates a watcher that starts at the current head of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `&Client`: Client used to query the node.
+    /// * `Int`: Number of confirmations before a payment is considered final.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nimiq_rpc::Client;
+    /// use nimiq_rpc::watcher::AddressWatcher;
+    /// let client = Client::new("http://seed-host.com:8648");
+    /// let mut watcher = AddressWatcher::new(&client, 10);
+    /// watcher.watch("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN");
+    /// let result = watcher.poll();
+    /// ```
+    pub fn new(client: &'a Client, confirmations: u64) -> AddressWatcher<'a> {
+        AddressWatcher {
+            client,
+            follower: ChainFollower::new(client, confirmations).full_transactions(true),
+            addresses: HashSet::new(),
+            include_mempool: true,
+            poll_interval: Duration::from_secs(1),
+            pending: HashSet::new(),
+            included: HashMap::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Starts scanning blocks at the given height instead of the current head.
+    pub fn from_height(mut self, height: u64) -> AddressWatcher<'a> {
+        self.follower = self.follower.from_height(height);
+        self
+    }
+
+    /// Enables or disables reporting of pending transactions from the mempool.
+    pub fn include_mempool(mut self, include_mempool: bool) -> AddressWatcher<'a> {
+        self.include_mempool = include_mempool;
+        self
+    }
+
+    /// Sets the delay between two polls when the watcher is used as an iterator.
+    pub fn poll_interval(mut self, interval: Duration) -> AddressWatcher<'a> {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Adds an address to the watched set. Both user friendly and hex addresses are accepted.
+    pub fn watch(&mut self, address: &str) {
+        self.addresses.insert(normalize(address));
+    }
+
+    /// Removes an address from the watched set.
+    pub fn unwatch(&mut self, address: &str) {
+        self.addresses.remove(&normalize(address));
+    }
+
+    /// Queries the node once and returns the payment events since the last poll.
+    ///
+    /// # Returns
+    ///
+    /// Vector of payment events in the order they should be processed.
+    pub fn poll(&mut self) -> Result<Vec<PaymentEvent>, Error> {
+        // The mempool is fetched first, a failure after the follower advanced would lose the
+        // events of the new blocks.
+        let mempool = if self.include_mempool {
+            Some(self.client.mempool_content_with_transactions()?)
+        } else {
+            None
+        };
+
+        let mut events = Vec::new();
+        for event in self.follower.poll()? {
+            match event {
+                ChainEvent::Applied(block) => self.apply(&block, &mut events),
+                ChainEvent::Reverted(block) => {
+                    self.remove_block(&block, PaymentStatus::Reverted, &mut events)
+                }
+                ChainEvent::Finalized(block) => {
+                    self.remove_block(&block, PaymentStatus::Finalized, &mut events)
+                }
+            }
+        }
+
+        if let Some(mempool) = mempool {
+            let hashes: HashSet<String> = mempool.iter().map(|tx| tx.hash.clone()).collect();
+            for tx in mempool {
+                if self.pending.contains(&tx.hash) || self.included.contains_key(&tx.hash) {
+                    continue;
+                }
+                if emit(&self.addresses, &tx, PaymentStatus::Pending, &mut events) {
+                    self.pending.insert(tx.hash);
+                }
+            }
+            self.pending.retain(|hash| hashes.contains(hash));
+        }
+
+        Ok(events)
+    }
+
+    fn apply(&mut self, block: &Block, events: &mut Vec<PaymentEvent>) {
+        for tx in self.included.values_mut() {
+            tx.confirmations = block.number - tx.block_number + 1;
+        }
+        for tx in self.included.values() {
+            emit(
+                &self.addresses,
+                tx,
+                PaymentStatus::Confirmed(tx.confirmations),
+                events,
+            );
+        }
+
+        if let TransactionSequence::Transactions(transactions) = &block.transactions {
+            for tx in transactions {
+                let mut tx = tx.clone();
+                tx.confirmations = 1;
+                if emit(&self.addresses, &tx, PaymentStatus::Confirmed(1), events) {
+                    self.pending.remove(&tx.hash);
+                    self.included.insert(tx.hash.clone(), tx);
+                }
+            }
+        }
+    }
+
+    fn remove_block(
+        &mut self,
+        block: &Block,
+        status: PaymentStatus,
+        events: &mut Vec<PaymentEvent>,
+    ) {
+        let hashes: Vec<String> = self
+            .included
+            .values()
+            .filter(|tx| tx.block_hash == block.hash)
+            .map(|tx| tx.hash.clone())
+            .collect();
+
+        for hash in hashes {
+            if let Some(tx) = self.included.remove(&hash) {
+                emit(&self.addresses, &tx, status, events);
+            }
+        }
+    }
+}
+
+fn emit(
+    addresses: &HashSet<String>,
+    tx: &Transaction,
+    status: PaymentStatus,
+    events: &mut Vec<PaymentEvent>,
+) -> bool {
+    let mut matched = false;
+
+    for (direction, address, hex) in &[
+        (Direction::Outgoing, &tx.from_address, &tx.from),
+        (Direction::Incoming, &tx.to_address, &tx.to),
+    ] {
+        let address = normalize(address);
+        if addresses.contains(&address) || addresses.contains(&normalize(hex)) {
+            events.push(PaymentEvent {
+                address,
+                direction: *direction,
+                status,
+                transaction: tx.clone(),
+            });
+            matched = true;
+        }
+    }
+
+    matched
+}
+
+pub(crate) fn normalize(address: &str) -> String {
+    let address: String = address.split_whitespace().collect();
+    if address.len() > 2 && address[..2].eq_ignore_ascii_case("NQ") {
+        address.to_uppercase()
+    } else {
+        address.to_lowercase()
+    }
+}
+
+impl<'a> Iterator for AddressWatcher<'a> {
+    type Item = Result<PaymentEvent, Error>;
+
+    /// Blocks until the next payment event is available, polling the node in between.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(Ok(event));
+            }
+
+            match self.poll() {
+                Ok(events) if events.is_empty() => thread::sleep(self.poll_interval),
+                Ok(events) => self.events.extend(events),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use std::io;
+use std::marker::PhantomData;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use jsonrpc::error::Error;
+use jsonrpc::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::albatross::primitives::{Block, BlockLog};
+use crate::transport::Transport;
+
+/// How long the connection thread waits for a message before sending queued requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// A transport that sends requests over a single WebSocket connection and receives
+/// notifications of subscriptions on it.
+///
+/// The connection is opened in the background and reopened whenever it drops, after which all
+/// active subscriptions are renewed. Requests that were in flight when the connection dropped
+/// fail, requests sent while it is down wait for the next connection until they time out.
+///
+/// The transport is a cheap handle, a clone can be passed to
+/// [`Client::new_with_transport`](crate::Client::new_with_transport) while another is used
+/// for subscriptions.
+///
+/// # Example
+///
+/// ```no_run
+/// use nimiq_rpc::albatross::Client;
+/// use nimiq_rpc::websocket::WebSocketTransport;
+/// let transport = WebSocketTransport::new("ws://seed-host.com:8648/ws").unwrap();
+/// let client = Client::new_with_transport(transport.clone());
+/// println!("starting at {}", client.block_number().unwrap());
+/// for block in transport.subscribe_head_blocks(false).unwrap() {
+///     println!("new head {}", block.unwrap().number);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct WebSocketTransport {
+    shared: Arc<Shared>,
+}
+
+impl WebSocketTransport {
+    /// Creates a transport for the node at `url`, e.g. `ws://127.0.0.1:8648/ws`.
+    ///
+    /// Fails if the URL does not use the `ws` scheme, TLS connections are not supported.
+    pub fn new(url: &str) -> Result<WebSocketTransport, Error> {
+        match url.split_once("://") {
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("ws") => {}
+            _ => {
+                return Err(io_error(
+                    io::ErrorKind::InvalidInput,
+                    "unsupported URL scheme, only ws:// is supported",
+                ))
+            }
+        }
+
+        let (commands, receiver) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            url: url.to_owned(),
+            timeout_ms: AtomicU64::new(30_000),
+            reconnect_delay_ms: AtomicU64::new(1_000),
+            next_id: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            connected: AtomicBool::new(false),
+            reconnects: AtomicU64::new(0),
+            commands: Mutex::new(commands),
+            pending: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        });
+
+        let connection = Arc::clone(&shared);
+        thread::spawn(move || connection.run(receiver));
+        Ok(WebSocketTransport { shared })
+    }
+
+    /// Sets how long a request waits for its response, 30 seconds by default.
+    pub fn timeout(self, timeout: Duration) -> WebSocketTransport {
+        self.shared
+            .timeout_ms
+            .store(timeout.as_millis() as u64, Ordering::Relaxed);
+        self
+    }
+
+    /// Sets how long to wait before reconnecting after the connection dropped, one second by
+    /// default.
+    pub fn reconnect_delay(self, delay: Duration) -> WebSocketTransport {
+        self.shared
+            .reconnect_delay_ms
+            .store(delay.as_millis() as u64, Ordering::Relaxed);
+        self
+    }
+
+    /// Returns whether the connection is currently open.
+    pub fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::SeqCst)
+    }
+
+    /// Returns how often the connection was reopened after it dropped.
+    pub fn reconnects(&self) -> u64 {
+        self.shared.reconnects.load(Ordering::SeqCst)
+    }
+
+    /// Closes the connection and ends all subscriptions.
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Subscribes to a method and returns its notifications.
+    ///
+    /// Results wrapped in `{"data": ..., "metadata": ...}` are unwrapped.
+    pub fn subscribe<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<Subscription<T>, Error> {
+        let (notifications, receiver) = mpsc::channel();
+        let (confirmation, confirmed) = mpsc::channel();
+        let subscription = self.shared.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let state = SubscriptionState {
+            method: method.to_owned(),
+            params,
+            server_id: None,
+            notifications,
+        };
+        let request = state.request(subscription);
+        self.shared.send(
+            subscription,
+            Pending::Subscribe {
+                subscription,
+                state: Some(state),
+                confirmation: Some(confirmation),
+            },
+            request,
+        )?;
+        self.shared.wait(subscription, &confirmed)?;
+
+        Ok(Subscription {
+            id: subscription,
+            receiver,
+            shared: Arc::clone(&self.shared),
+            marker: PhantomData,
+        })
+    }
+
+    /// Subscribes to new head blocks, with their transactions if `include_body` is set.
+    pub fn subscribe_head_blocks(&self, include_body: bool) -> Result<Subscription<Block>, Error> {
+        self.subscribe("subscribeForHeadBlock", vec![json!(include_body)])
+    }
+
+    /// Subscribes to the hashes of new head blocks.
+    pub fn subscribe_head_hashes(&self) -> Result<Subscription<String>, Error> {
+        self.subscribe("subscribeForHeadBlockHash", vec![])
+    }
+
+    /// Subscribes to the transactions and inherents of a set of addresses in new and reverted
+    /// blocks.
+    ///
+    /// `log_types` restricts the logs to types such as `transfer`, all types are reported if
+    /// it is empty.
+    pub fn subscribe_logs(
+        &self,
+        addresses: &[&str],
+        log_types: &[&str],
+    ) -> Result<Subscription<BlockLog>, Error> {
+        self.subscribe(
+            "subscribeForLogsByAddressesAndTypes",
+            vec![json!(addresses), json!(log_types)],
+        )
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let (sender, receiver) = mpsc::channel();
+        let id = self.shared.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut message = serde_json::to_value(request)?;
+        message["id"] = json!(id);
+        self.shared.send(
+            id,
+            Pending::Request {
+                id: request.id.clone(),
+                sender,
+            },
+            message.to_string(),
+        )?;
+        self.shared.wait(id, &receiver)
+    }
+}
+
+/// Notifications of a subscription, ending when the transport is closed.
+///
+/// The subscription survives reconnects. Dropping it stops delivering its notifications and
+/// unsubscribes on the node.
+pub struct Subscription<T> {
+    id: u64,
+    receiver: Receiver<Value>,
+    shared: Arc<Shared>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Subscription<T> {
+    /// Waits up to `timeout` for the next notification.
+    ///
+    /// Returns `None` if there was none or the transport was closed.
+    pub fn next_timeout(&self, timeout: Duration) -> Option<Result<T, Error>> {
+        self.receiver.recv_timeout(timeout).ok().map(decode)
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for Subscription<T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        self.receiver.recv().ok().map(decode)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let state = self.shared.subscriptions().remove(&self.id);
+        if let Some(server_id) = state.and_then(|state| state.server_id) {
+            self.shared.unsubscribe(server_id);
+        }
+    }
+}
+
+struct Shared {
+    url: String,
+    timeout_ms: AtomicU64,
+    reconnect_delay_ms: AtomicU64,
+    next_id: AtomicU64,
+    closed: AtomicBool,
+    connected: AtomicBool,
+    reconnects: AtomicU64,
+    commands: Mutex<Sender<String>>,
+    /// Requests waiting for their response, by the id sent to the node.
+    pending: Mutex<HashMap<u64, Pending>>,
+    /// Confirmed subscriptions, by the id of their [`Subscription`].
+    subscriptions: Mutex<HashMap<u64, SubscriptionState>>,
+}
+
+enum Pending {
+    Request {
+        /// The id of the caller's request, restored in the response.
+        id: Value,
+        sender: Sender<Result<Response, Error>>,
+    },
+    Subscribe {
+        subscription: u64,
+        /// Set for new subscriptions, `None` when renewing one after a reconnect.
+        state: Option<SubscriptionState>,
+        confirmation: Option<Sender<Result<(), Error>>>,
+    },
+}
+
+struct SubscriptionState {
+    method: String,
+    params: Vec<Value>,
+    /// The id the node sends notifications with, `None` until the subscription is confirmed.
+    server_id: Option<Value>,
+    notifications: Sender<Value>,
+}
+
+impl SubscriptionState {
+    fn request(&self, id: u64) -> String {
+        json!({"jsonrpc": "2.0", "id": id, "method": self.method, "params": self.params})
+            .to_string()
+    }
+}
+
+impl Shared {
+    fn send(&self, id: u64, pending: Pending, message: String) -> Result<(), Error> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(io_error(io::ErrorKind::NotConnected, "transport closed"));
+        }
+        self.pending().insert(id, pending);
+        let _ = lock(&self.commands).send(message);
+        Ok(())
+    }
+
+    fn wait<T>(&self, id: u64, receiver: &Receiver<Result<T, Error>>) -> Result<T, Error> {
+        let timeout = Duration::from_millis(self.timeout_ms.load(Ordering::Relaxed));
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                self.pending().remove(&id);
+                Err(io_error(io::ErrorKind::TimedOut, "request timed out"))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(io_error(
+                io::ErrorKind::ConnectionAborted,
+                "transport closed",
+            )),
+        }
+    }
+
+    /// Asks the node to end a subscription, without waiting for its answer.
+    fn unsubscribe(&self, server_id: Value) {
+        if self.closed.load(Ordering::SeqCst) {
+            return;
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let request =
+            json!({"jsonrpc": "2.0", "id": id, "method": "unsubscribe", "params": [server_id]});
+        let _ = lock(&self.commands).send(request.to_string());
+    }
+
+    fn pending(&self) -> MutexGuard<'_, HashMap<u64, Pending>> {
+        lock(&self.pending)
+    }
+
+    fn subscriptions(&self) -> MutexGuard<'_, HashMap<u64, SubscriptionState>> {
+        lock(&self.subscriptions)
+    }
+
+    /// Returns whether the transport was closed or every handle to it was dropped.
+    fn abandoned(self: &Arc<Self>) -> bool {
+        self.closed.load(Ordering::SeqCst) || Arc::strong_count(self) == 1
+    }
+
+    /// Keeps the connection open until the transport is abandoned.
+    fn run(self: Arc<Self>, commands: Receiver<String>) {
+        let mut connected_before = false;
+        while !self.abandoned() {
+            if let Ok((mut socket, _)) = tungstenite::connect(self.url.as_str()) {
+                if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+                    let _ = stream.set_read_timeout(Some(POLL_INTERVAL));
+                }
+                if connected_before {
+                    self.reconnects.fetch_add(1, Ordering::SeqCst);
+                }
+                connected_before = true;
+                self.connected.store(true, Ordering::SeqCst);
+                let _ = self.serve(&mut socket, &commands);
+                self.connected.store(false, Ordering::SeqCst);
+                self.fail_pending();
+            }
+            if self.abandoned() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(
+                self.reconnect_delay_ms.load(Ordering::Relaxed),
+            ));
+        }
+        self.fail_pending();
+        self.subscriptions().clear();
+    }
+
+    fn serve(
+        self: &Arc<Self>,
+        socket: &mut Socket,
+        commands: &Receiver<String>,
+    ) -> Result<(), Box<tungstenite::Error>> {
+        let renewals: Vec<(u64, u64, String)> = self
+            .subscriptions()
+            .iter_mut()
+            .map(|(subscription, state)| {
+                state.server_id = None;
+                let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+                (*subscription, id, state.request(id))
+            })
+            .collect();
+        for (subscription, id, request) in renewals {
+            self.pending().insert(
+                id,
+                Pending::Subscribe {
+                    subscription,
+                    state: None,
+                    confirmation: None,
+                },
+            );
+            socket.send(Message::Text(request))?;
+        }
+
+        loop {
+            if self.abandoned() {
+                let _ = socket.close(None);
+                return Ok(());
+            }
+            loop {
+                match commands.try_recv() {
+                    Ok(message) => socket.send(Message::Text(message))?,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+            match socket.read() {
+                Ok(Message::Text(message)) => self.dispatch(&message),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Routes a message from the node to the request or subscription it belongs to.
+    fn dispatch(&self, message: &str) {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        if let Some(id) = message.get("id").and_then(Value::as_u64) {
+            let pending = self.pending().remove(&id);
+            let response: Response = match serde_json::from_value(message) {
+                Ok(response) => response,
+                Err(_) => return,
+            };
+            match pending {
+                Some(Pending::Request { id, sender }) => {
+                    let _ = sender.send(Ok(Response { id, ..response }));
+                }
+                Some(Pending::Subscribe {
+                    subscription,
+                    state,
+                    confirmation,
+                }) => {
+                    let result = response.into_result::<Value>().map(unwrap_data);
+                    match (&result, state) {
+                        (Ok(server_id), Some(mut state)) => {
+                            state.server_id = Some(server_id.clone());
+                            self.subscriptions().insert(subscription, state);
+                        }
+                        (Ok(server_id), None) => {
+                            if let Some(state) = self.subscriptions().get_mut(&subscription) {
+                                state.server_id = Some(server_id.clone());
+                            }
+                        }
+                        (Err(_), _) => {}
+                    }
+                    if let Some(confirmation) = confirmation {
+                        let _ = confirmation.send(result.map(|_| ()));
+                    }
+                }
+                None => {}
+            }
+        } else if let Some(params) = message.get("params") {
+            let server_id = &params["subscription"];
+            let subscriptions = self.subscriptions();
+            let state = subscriptions
+                .values()
+                .find(|state| state.server_id.as_ref() == Some(server_id));
+            if let Some(state) = state {
+                let _ = state.notifications.send(params["result"].clone());
+            }
+        }
+    }
+
+    /// Fails the requests still waiting for a response on a connection that dropped.
+    fn fail_pending(&self) {
+        for (_, pending) in self.pending().drain() {
+            let error = || io_error(io::ErrorKind::ConnectionReset, "connection lost");
+            match pending {
+                Pending::Request { sender, .. } => {
+                    let _ = sender.send(Err(error()));
+                }
+                Pending::Subscribe {
+                    confirmation: Some(confirmation),
+                    ..
+                } => {
+                    let _ = confirmation.send(Err(error()));
+                }
+                Pending::Subscribe { .. } => {}
+            }
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    Ok(serde_json::from_value(unwrap_data(value))?)
+}
+
+/// Unwraps the `data` of results in the `{"data": ..., "metadata": ...}` form of Albatross.
+fn unwrap_data(value: Value) -> Value {
+    match value {
+        Value::Object(mut object)
+            if object.contains_key("data") && object.contains_key("metadata") =>
+        {
+            object.remove("data").unwrap_or_default()
+        }
+        value => value,
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn io_error(kind: io::ErrorKind, message: &str) -> Error {
+    Error::Hyper(io::Error::new(kind, message).into())
+}
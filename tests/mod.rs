@@ -1,45 +1,196 @@
 #[cfg(test)]
 mod tests {
+    use std::convert::TryInto;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use nimiq_rpc::testing::{MockServer, RecordingTransport, ReplayTransport, SimulatedChain};
     use nimiq_rpc::*;
+    use serde_json::{json, Value};
+
+    const ADDRESS: &str = "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN";
+    const OTHER_ADDRESS: &str = "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH3";
+    const BLOCK_HASH: &str = "a9284b441b56e93de62f557414cc9b850bad2bd30cf84b013cfe2ef6e11b6da6";
+    const TX_HASH: &str = "465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554";
+
+    fn hash(seed: u64) -> String {
+        format!("{:064x}", seed)
+    }
 
-    fn client() -> Client {
-        let host = "http://seed-host.com:8648";
+    fn transaction(hash: &str, block: &Value, index: u64, from: &str, to: &str) -> Value {
+        json!({
+            "hash": hash,
+            "blockHash": block["hash"],
+            "blockNumber": block["number"],
+            "timestamp": block["timestamp"],
+            "confirmations": 1,
+            "transactionIndex": index,
+            "from": "ad25610feb43d75307763d3f010822a757027429",
+            "fromAddress": from,
+            "to": "824aa01033c89595479bab9d8deb4fc4f3f21c8a",
+            "toAddress": to,
+            "value": 100000,
+            "fee": 138,
+            "data": null,
+            "flags": 0
+        })
+    }
 
-        if host == "http://seed-host.com:8648" || host == "http://seed-host.com:8648/" {
-            panic!("You have to change the host to your RPC server in the tests!")
+    fn pending(hash: &str, from: &str, to: &str) -> Value {
+        let mut tx = transaction(hash, &Value::Null, 0, from, to);
+        let fields = tx.as_object_mut().unwrap();
+        for field in &["blockHash", "blockNumber", "timestamp"] {
+            fields.remove(*field);
         }
-        Client::new(&host)
+        fields.insert("confirmations".to_owned(), json!(0));
+        tx
+    }
+
+    fn block(number: u64, hash: &str, parent_hash: &str) -> Value {
+        json!({
+            "number": number,
+            "hash": hash,
+            "pow": "0000000000d5c0b54b1e7e8a6dbb7b8bd3ea3d68f6ec9e3bb1f1a2a68a3d3cb5",
+            "parentHash": parent_hash,
+            "nonce": 2066433,
+            "bodyHash": "3fbf0c8a8c4bd5f4ae5b8ee59a0b2c14f1d9ad70ba5e3e1fbd0d9c64dfcbc1e8",
+            "accountsHash": "71efb3ec8d0e2d0c4b51e71b0e5b7d7a2a8f1e55bf5b6a5bcbc0e4a1e5f2f4e4",
+            "miner": "f2d9e3a3c7cc9e3b4d6a56dcf8b9d6e6c8f1e5e2",
+            "minerAddress": "NQ39 YB4E 78E7 RJF3 NKDA ATEF HEEN UT4F 1RF2",
+            "difficulty": "58290.86017846",
+            "extraData": "",
+            "size": 1402,
+            "timestamp": 1585049478,
+            "transactions": []
+        })
+    }
+
+    /// Blocks served by `getBlockByNumber`, the last one is the head.
+    type Chain = Arc<Mutex<Vec<Value>>>;
+
+    fn chain(server: &MockServer, blocks: Vec<Value>) -> Chain {
+        let chain = Arc::new(Mutex::new(blocks));
+
+        let head = Arc::clone(&chain);
+        server.on_call("blockNumber", move |_| {
+            Ok(head.lock().unwrap().last().unwrap()["number"].clone())
+        });
+
+        let blocks = Arc::clone(&chain);
+        server.on_call("getBlockByNumber", move |params| {
+            let blocks = blocks.lock().unwrap();
+            Ok(blocks
+                .iter()
+                .find(|block| block["number"] == params[0])
+                .cloned()
+                .unwrap_or(Value::Null))
+        });
+
+        chain
+    }
+
+    fn linear_chain(from: u64, to: u64, seed: u64) -> Vec<Value> {
+        (from..=to)
+            .map(|number| {
+                let parent = if number == from {
+                    hash(number - 1)
+                } else {
+                    hash(seed + number - 1)
+                };
+                block(number, &hash(seed + number), &parent)
+            })
+            .collect()
     }
 
     #[test]
     fn accounts() {
-        let client = client();
-        client.accounts().unwrap();
+        let server = MockServer::start();
+        server.on(
+            "accounts",
+            json!([{"id": "f925107376081be421f52604b8ce8f94b0d71a9b", "address": ADDRESS, "balance": 1200000, "type": 0}]),
+        );
+        let accounts = server.client().accounts().unwrap();
+        assert_eq!(accounts[0].address, ADDRESS);
+        assert_eq!(accounts[0].balance, 1200000);
     }
 
     #[test]
     fn block_number() {
-        let client = client();
-        client.block_number().unwrap();
+        let server = MockServer::start();
+        server.on("blockNumber", 882418);
+        assert_eq!(server.client().block_number().unwrap(), 882418);
     }
 
     #[test]
     fn consensus() {
-        let client = client();
-        assert_eq!(client.consensus().unwrap(), "established");
+        let server = MockServer::start();
+        server.on("consensus", "established");
+        assert_eq!(server.client().consensus().unwrap(), "established");
+    }
+
+    #[test]
+    fn create_account() {
+        let server = MockServer::start();
+        server.on(
+            "createAccount",
+            json!({"id": "f925107376081be421f52604b8ce8f94b0d71a9b", "address": ADDRESS, "publicKey": "e1c6a95c0d26e2bd74c6e24e8a5b3d3a7e88ad7f1f9a0c5c6e2ad3fc6b63f5e1"}),
+        );
+        assert_eq!(server.client().create_account().unwrap().address, ADDRESS);
+    }
+
+    #[test]
+    fn create_raw_transaction() {
+        let server = MockServer::start();
+        server.on("createRawTransaction", "00c3c0d1af80b84c3b3de4e3d79d5c8cc950e044098c969953d68bf9cee68d7b53305dbaac7514a06dae935e40d599caf1bd8a243c00000000000000010000000000000001000dc2e201");
+        let tx = primitives::OutgoingTransaction {
+            from: ADDRESS,
+            to: OTHER_ADDRESS,
+            value: 100,
+            fee: 0,
+        };
+        server.client().create_raw_transaction(&tx).unwrap();
+        assert_eq!(
+            server.requests()[0].params,
+            vec![json!({"from": ADDRESS, "to": OTHER_ADDRESS, "value": 100, "fee": 0})]
+        );
+    }
+
+    #[test]
+    fn get_account() {
+        let server = MockServer::start();
+        server.on(
+            "getAccount",
+            json!({"id": "ad25610feb43d75307763d3f010822a757027429", "address": ADDRESS, "balance": 0, "type": 0}),
+        );
+        let account = server
+            .client()
+            .get_account("ad25610feb43d75307763d3f010822a757027429")
+            .unwrap();
+        assert_eq!(account.id, "ad25610feb43d75307763d3f010822a757027429");
     }
 
-    // #[test]
-    // fn create_account() {
-    //   let client = client();
-    //   client.create_account().unwrap();
-    // }
+    #[test]
+    fn get_balance() {
+        let server = MockServer::start();
+        server.on("getBalance", 1200000);
+        assert_eq!(
+            server
+                .client()
+                .get_balance("ad25610feb43d75307763d3f010822a757027429")
+                .unwrap(),
+            1200000
+        );
+    }
 
     #[test]
     fn get_block_by_hash() {
-        let client = client();
+        let server = MockServer::start();
+        let mut block = block(882418, BLOCK_HASH, &hash(882417));
+        block["transactions"] = json!([TX_HASH]);
+        server.on("getBlockByHash", block);
         assert_eq!(
-            client
+            server
+                .client()
                 .get_block_by_hash(
                     "A9284B441B56E93DE62F557414CC9B850BAD2BD30CF84B013CFE2EF6E11B6DA6",
                     false
@@ -48,52 +199,106 @@ mod tests {
                 .number,
             882418
         );
+        assert_eq!(
+            server.requests()[0].params,
+            vec![
+                json!("A9284B441B56E93DE62F557414CC9B850BAD2BD30CF84B013CFE2EF6E11B6DA6"),
+                json!(false)
+            ]
+        );
     }
 
     #[test]
     fn get_block_and_tx_by_hash() {
-        let client = client();
-        assert_eq!(
-            client
-                .get_block_by_hash(
-                    "A9284B441B56E93DE62F557414CC9B850BAD2BD30CF84B013CFE2EF6E11B6DA6",
-                    true
-                )
-                .unwrap()
-                .number,
-            882418
-        );
+        let server = MockServer::start();
+        let mut block = block(882418, BLOCK_HASH, &hash(882417));
+        block["transactions"] = json!([transaction(TX_HASH, &block, 0, ADDRESS, OTHER_ADDRESS)]);
+        server.on("getBlockByHash", block);
+        let block = server.client().get_block_by_hash(BLOCK_HASH, true).unwrap();
+        match block.transactions {
+            primitives::TransactionSequence::Transactions(transactions) => {
+                assert_eq!(transactions[0].hash, TX_HASH)
+            }
+            primitives::TransactionSequence::BlockHashes(_) => panic!("expected transactions"),
+        }
     }
 
     #[test]
     fn get_block_by_number() {
-        let client = client();
+        let server = MockServer::start();
+        server.on("getBlockByNumber", block(882418, BLOCK_HASH, &hash(882417)));
         assert_eq!(
-            client.get_block_by_number(882418, false).unwrap().hash,
-            "a9284b441b56e93de62f557414cc9b850bad2bd30cf84b013cfe2ef6e11b6da6"
+            server
+                .client()
+                .get_block_by_number(882418, false)
+                .unwrap()
+                .hash,
+            BLOCK_HASH
+        );
+        assert_eq!(
+            server.requests()[0].params,
+            vec![json!(882418), json!(false)]
         );
     }
 
     #[test]
     fn get_block_and_tx_by_number() {
-        let client = client();
+        let server = MockServer::start();
+        let mut block = block(882418, BLOCK_HASH, &hash(882417));
+        block["transactions"] = json!([transaction(TX_HASH, &block, 0, ADDRESS, OTHER_ADDRESS)]);
+        server.on("getBlockByNumber", block);
+        assert_eq!(
+            server
+                .client()
+                .get_block_by_number(882418, true)
+                .unwrap()
+                .hash,
+            BLOCK_HASH
+        );
         assert_eq!(
-            client.get_block_by_number(882418, true).unwrap().hash,
-            "a9284b441b56e93de62f557414cc9b850bad2bd30cf84b013cfe2ef6e11b6da6"
+            server.requests()[0].params,
+            vec![json!(882418), json!(true)]
         );
     }
 
     #[test]
     fn get_block_template() {
-        let client = client();
-        client.get_block_template().unwrap();
+        let server = MockServer::start();
+        server.on(
+            "getBlockTemplate",
+            json!({
+                "header": {
+                    "version": 1,
+                    "prevHash": BLOCK_HASH,
+                    "interlinkHash": hash(1),
+                    "accountsHash": hash(2),
+                    "nBits": 486604799,
+                    "height": 882419
+                },
+                "interlink": "0000",
+                "target": 486604799,
+                "body": {
+                    "hash": hash(3),
+                    "minerAddr": "ad25610feb43d75307763d3f010822a757027429",
+                    "extraData": "",
+                    "transactions": [],
+                    "merkleHashes": [],
+                    "prunedAccounts": []
+                }
+            }),
+        );
+        let template = server.client().get_block_template().unwrap();
+        assert_eq!(template.header.height, 882419);
+        assert_eq!(template.header.prev_hash, BLOCK_HASH);
     }
 
     #[test]
     fn get_block_transaction_count_by_hash() {
-        let client = client();
+        let server = MockServer::start();
+        server.on("getBlockTransactionCountByHash", 2);
         assert_eq!(
-            client
+            server
+                .client()
                 .get_block_transaction_count_by_hash(
                     "A9284B441B56E93DE62F557414CC9B850BAD2BD30CF84B013CFE2EF6E11B6DA6"
                 )
@@ -104,50 +309,78 @@ mod tests {
 
     #[test]
     fn get_block_transaction_count_by_number() {
-        let client = client();
+        let server = MockServer::start();
+        server.on("getBlockTransactionCountByNumber", 2);
         assert_eq!(
-            client
+            server
+                .client()
                 .get_block_transaction_count_by_number(882418)
                 .unwrap(),
             2
         );
+        assert_eq!(server.requests()[0].params, vec![json!(882418)]);
     }
 
     #[test]
     fn get_transaction_by_block_hash_and_index() {
-        let client = client();
+        let server = MockServer::start();
+        let block = block(
+            76415,
+            "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f",
+            &hash(76414),
+        );
+        server.on(
+            "getTransactionByBlockHashAndIndex",
+            transaction(TX_HASH, &block, 20, ADDRESS, OTHER_ADDRESS),
+        );
         assert_eq!(
-            client
+            server
+                .client()
                 .get_transaction_by_block_hash_and_index(
                     "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f",
                     20
                 )
                 .unwrap()
                 .hash,
-            "465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554"
+            TX_HASH
         );
     }
 
     #[test]
     fn get_transaction_by_block_number_and_index() {
-        let client = client();
+        let server = MockServer::start();
+        let block = block(76415, &hash(76415), &hash(76414));
+        server.on(
+            "getTransactionByBlockNumberAndIndex",
+            transaction(TX_HASH, &block, 20, ADDRESS, OTHER_ADDRESS),
+        );
         assert_eq!(
-            client
+            server
+                .client()
                 .get_transaction_by_block_number_and_index(76415, 20)
                 .unwrap()
                 .hash,
-            "465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554"
+            TX_HASH
         );
+        assert_eq!(server.requests()[0].params, vec![json!(76415), json!(20)]);
     }
 
     #[test]
     fn get_transaction_by_hash() {
-        let client = client();
+        let server = MockServer::start();
+        let block = block(
+            76415,
+            "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f",
+            &hash(76414),
+        );
+        server.on(
+            "getTransactionByHash",
+            transaction(TX_HASH, &block, 20, ADDRESS, OTHER_ADDRESS),
+        );
         assert_eq!(
-            client
-                .get_transaction_by_hash(
-                    "465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554"
-                )
+            server
+                .client()
+                .get_transaction_by_hash(TX_HASH)
                 .unwrap()
                 .block_hash,
             "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f"
@@ -156,12 +389,22 @@ mod tests {
 
     #[test]
     fn get_transaction_receipt() {
-        let client = client();
+        let server = MockServer::start();
+        server.on(
+            "getTransactionReceipt",
+            json!({
+                "transactionHash": TX_HASH,
+                "transactionIndex": 20,
+                "blockNumber": 76415,
+                "blockHash": "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f",
+                "confirmations": 806004,
+                "timestamp": 1523412456
+            }),
+        );
         assert_eq!(
-            client
-                .get_transaction_receipt(
-                    "465a63b73aa0b9b54b777be9a585ea00b367a17898ad520e1f22cb2c986ff554"
-                )
+            server
+                .client()
+                .get_transaction_receipt(TX_HASH)
                 .unwrap()
                 .block_hash,
             "dfe7d166f2c86bd10fa4b1f29cd06c13228f893167ce9826137c85758645572f"
@@ -170,122 +413,2373 @@ mod tests {
 
     #[test]
     fn get_transactions_by_address() {
-        let client = client();
-        client
-            .get_transactions_by_address("NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GN", 5)
+        let server = MockServer::start();
+        let block = block(76415, &hash(76415), &hash(76414));
+        server.on(
+            "getTransactionsByAddress",
+            json!([transaction(TX_HASH, &block, 20, ADDRESS, OTHER_ADDRESS)]),
+        );
+        let transactions = server
+            .client()
+            .get_transactions_by_address(ADDRESS, 5)
             .unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(server.requests()[0].params, vec![json!(ADDRESS), json!(5)]);
     }
 
     #[test]
     fn get_work() {
-        let client = client();
-        client.get_work().unwrap();
+        let server = MockServer::start();
+        server.on(
+            "getWork",
+            json!({"data": "0001", "suffix": "0000", "target": 503371226, "algorithm": "nimiq-argon2"}),
+        );
+        assert_eq!(server.client().get_work().unwrap().target, 503371226);
     }
 
     #[test]
     fn hashrate() {
-        let client = client();
-        client.hashrate().unwrap();
+        let server = MockServer::start();
+        server.on("hashrate", 52982.2731);
+        assert_eq!(server.client().hashrate().unwrap(), 52982.2731);
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn log() {
-        let client = client();
-        assert_eq!(client.log("*", "log").unwrap(), true);
+        let server = MockServer::start();
+        server.on("log", true);
+        assert_eq!(server.client().log("*", "log").unwrap(), true);
+        assert_eq!(server.requests()[0].params, vec![json!("*"), json!("log")]);
+    }
+
+    #[test]
+    fn mempool() {
+        let server = MockServer::start();
+        server.on(
+            "mempool",
+            json!({"total": 3, "buckets": [10, 1], "10": 1, "1": 2}),
+        );
+        let mempool = server.client().mempool().unwrap();
+        let bucketed: u64 = mempool.buckets.iter().map(|&b| mempool.bucket(b)).sum();
+        assert_eq!(bucketed, mempool.total);
     }
 
     #[test]
     fn mempool_content() {
-        let client = client();
-        client.mempool_content().unwrap();
+        let server = MockServer::start();
+        server.on("mempoolContent", json!([TX_HASH]));
+        assert_eq!(server.client().mempool_content().unwrap(), vec![TX_HASH]);
+    }
+
+    #[test]
+    fn mempool_content_with_transactions() {
+        let server = MockServer::start();
+        server.on(
+            "mempoolContent",
+            json!([pending(TX_HASH, ADDRESS, OTHER_ADDRESS)]),
+        );
+        let transactions = server.client().mempool_content_with_transactions().unwrap();
+        assert_eq!(transactions[0].block_number, 0);
+        assert_eq!(server.requests()[0].params, vec![json!(true)]);
     }
 
     #[test]
     fn miner_address() {
-        let client = client();
-        client.miner_address().unwrap();
+        let server = MockServer::start();
+        server.on("minerAddress", ADDRESS);
+        assert_eq!(server.client().miner_address().unwrap(), ADDRESS);
     }
 
     #[test]
     fn miner_threads() {
-        let client = client();
-        client.miner_threads().unwrap();
+        let server = MockServer::start();
+        server.on("minerThreads", 2);
+        assert_eq!(server.client().miner_threads().unwrap(), 2);
     }
 
     #[test]
     fn miner_threads_with_update() {
-        let client = client();
-        assert_eq!(client.miner_threads_with_update(1).unwrap(), 1);
+        let server = MockServer::start();
+        server.on_call("minerThreads", |params| Ok(params[0].clone()));
+        assert_eq!(server.client().miner_threads_with_update(1).unwrap(), 1);
     }
 
     #[test]
     fn min_fee_per_byte() {
-        let client = client();
-        client.min_fee_per_byte().unwrap();
+        let server = MockServer::start();
+        server.on("minFeePerByte", 0);
+        assert_eq!(server.client().min_fee_per_byte().unwrap(), 0);
     }
 
     #[test]
     fn min_fee_per_byte_with_update() {
-        let client = client();
-        assert_eq!(client.min_fee_per_byte_with_update(1).unwrap(), 1);
+        let server = MockServer::start();
+        server.on_call("minFeePerByte", |params| Ok(params[0].clone()));
+        assert_eq!(server.client().min_fee_per_byte_with_update(1).unwrap(), 1);
     }
 
     #[test]
     fn mining() {
-        let client = client();
-        client.mining().unwrap();
+        let server = MockServer::start();
+        server.on("mining", false);
+        assert!(!server.client().mining().unwrap());
     }
 
     #[test]
     fn peer_count() {
-        let client = client();
-        client.peer_count().unwrap();
+        let server = MockServer::start();
+        server.on("peerCount", 6);
+        assert_eq!(server.client().peer_count().unwrap(), 6);
     }
 
     #[test]
     fn peer_list() {
-        let client = client();
-        client.peer_list().unwrap();
+        let server = MockServer::start();
+        server.on(
+            "peerList",
+            json!([{
+                "id": "a400c3825edb8e00f1d99dea5299bce8",
+                "address": "wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8",
+                "addressState": 2,
+                "connectionState": 5,
+                "version": 2,
+                "timeOffset": -3,
+                "headHash": BLOCK_HASH,
+                "latency": 52,
+                "rx": 3640,
+                "tx": 3287
+            }, {
+                "id": "e38f5e2d0d3f0ec4b6e7b4c8f3b0c7a1",
+                "address": "wss://seed-1.nimiq.com:8443/e38f5e2d0d3f0ec4b6e7b4c8f3b0c7a1",
+                "addressState": 1
+            }]),
+        );
+        let peers = server.client().peer_list().unwrap();
+        assert_eq!(peers[0].latency, Some(52));
+        assert_eq!(peers[1].latency, None);
     }
 
     #[test]
     fn peer_state() {
-        let client = client();
-        client
+        let server = MockServer::start();
+        server.on(
+            "peerState",
+            json!({
+                "id": "a400c3825edb8e00f1d99dea5299bce8",
+                "address": "wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8",
+                "addressState": 2
+            }),
+        );
+        server
+            .client()
             .peer_state("wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8")
             .unwrap();
     }
 
     #[test]
     fn peer_state_with_update() {
-        let client = client();
-        client
+        let server = MockServer::start();
+        server.on(
+            "peerState",
+            json!({
+                "id": "a400c3825edb8e00f1d99dea5299bce8",
+                "address": "wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8",
+                "addressState": 1
+            }),
+        );
+        server
+            .client()
             .peer_state_with_update(
                 "wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8",
                 "connect",
             )
             .unwrap();
+        assert_eq!(
+            server.requests()[0].params,
+            vec![
+                json!("wss://urp.best:8443/a400c3825edb8e00f1d99dea5299bce8"),
+                json!("connect")
+            ]
+        );
     }
 
     #[test]
     fn pool_confirmed_balance() {
-        let client = client();
-        client.pool_confirmed_balance().unwrap();
+        let server = MockServer::start();
+        server.on("poolConfirmedBalance", 12000);
+        assert_eq!(server.client().pool_confirmed_balance().unwrap(), 12000);
     }
 
     #[test]
     fn pool_connection_state() {
-        let client = client();
-        client.pool_connection_state().unwrap();
+        let server = MockServer::start();
+        server.on("poolConnectionState", 2);
+        assert_eq!(server.client().pool_connection_state().unwrap(), 2);
+    }
+
+    #[test]
+    fn send_raw_transaction() {
+        let server = MockServer::start();
+        server.on("sendRawTransaction", TX_HASH);
+        assert_eq!(
+            server
+                .client()
+                .send_raw_transaction("00c3c0d1af80")
+                .unwrap(),
+            TX_HASH
+        );
+    }
+
+    #[test]
+    fn send_transaction() {
+        let server = MockServer::start();
+        server.on("sendTransaction", TX_HASH);
+        let tx = primitives::OutgoingTransaction {
+            from: ADDRESS,
+            to: OTHER_ADDRESS,
+            value: 100,
+            fee: 0,
+        };
+        assert_eq!(server.client().send_transaction(&tx).unwrap(), TX_HASH);
     }
 
     #[test]
+    fn submit_block() {
+        let server = MockServer::start();
+        server.on("submitBlock", ());
+        server.client().submit_block("0da1").unwrap();
+        assert_eq!(server.requests()[0].params, vec![json!("0da1")]);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn syncing() {
-        let client = client();
-        let state = client.syncing().unwrap();
-        match state {
+        let server = MockServer::start();
+        server.on("syncing", false);
+        match server.client().syncing().unwrap() {
             primitives::Syncing::IsSyncing(result) => assert_eq!(result, false),
-            primitives::Syncing::Pending(_) => assert!(true),
+            primitives::Syncing::Pending(_) => panic!("expected not syncing"),
+        }
+
+        server.on(
+            "syncing",
+            json!({"startingBlock": 1, "currentBlock": 12345, "highestBlock": 23456}),
+        );
+        match server.client().syncing().unwrap() {
+            primitives::Syncing::IsSyncing(_) => panic!("expected sync progress"),
+            primitives::Syncing::Pending(_) => {}
+        }
+    }
+
+    #[test]
+    fn rpc_error() {
+        let server = MockServer::start();
+        server.on_error("getBalance", -32602, "Invalid params");
+        match server.client().get_balance("invalid") {
+            Err(jsonrpc::error::Error::Rpc(err)) => assert_eq!(err.code, -32602),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn method_not_found() {
+        let server = MockServer::start();
+        match server.client().block_number() {
+            Err(jsonrpc::error::Error::Rpc(err)) => assert_eq!(err.code, -32601),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn disconnect() {
+        let server = MockServer::start();
+        server.on_disconnect("blockNumber");
+        assert!(server.client().block_number().is_err());
+    }
+
+    #[test]
+    fn latency() {
+        let server = MockServer::start();
+        server
+            .on("blockNumber", 1)
+            .latency("blockNumber", Duration::from_millis(200));
+        let start = Instant::now();
+        server.client().block_number().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn address_validation() {
+        let hex = address::to_hex(ADDRESS).unwrap();
+        assert_eq!(address::to_user_friendly(&hex).unwrap(), ADDRESS);
+        assert!(address::is_valid(ADDRESS));
+        assert!(address::is_valid(&hex));
+        assert!(address::is_valid(&ADDRESS.to_lowercase()));
+
+        let malformed = [
+            "",
+            "NQ",
+            "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GM",
+            "NQ69-9A4AMB83HXDQ4J46BH5R4JFFQMA9C3G",
+            "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3G!",
+            "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3G\u{e9}",
+            "NQ€9A4AMB83HXDQ4J46BH5R4JFFQMA9C3G",
+            "€€€€€€€€€€€€",
+            "NQ69 9A4A MB83 HXDQ 4J46 BH5R 4JFF QMA9 C3GNX",
+            "zz25610feb43d75307763d3f010822a757027429",
+            "ad25610feb43d75307763d3f010822a7570274",
+        ];
+        for address in &malformed {
+            assert!(!address::is_valid(address), "{:?} is valid", address);
+            assert_eq!(address::to_hex(address), None);
+        }
+        assert_eq!(malformed[3].len(), 36);
+        assert_eq!(malformed[6].len(), 36);
+    }
+
+    #[test]
+    fn chain_follower() {
+        let server = MockServer::start();
+        chain(&server, linear_chain(882417, 882418, 0));
+        let client = server.client();
+        let mut follower = follower::ChainFollower::new(&client, 10).from_height(882417);
+        match &follower.poll().unwrap()[..] {
+            [follower::ChainEvent::Applied(parent), follower::ChainEvent::Applied(block)] => {
+                assert_eq!(block.parent_hash, parent.hash);
+                assert_eq!(block.number, 882418);
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert!(follower.poll().unwrap().is_empty());
+    }
+
+    #[test]
+    fn chain_follower_reorg() {
+        let server = MockServer::start();
+        let blocks = chain(&server, linear_chain(1, 3, 0));
+        let client = server.client();
+        let mut follower = follower::ChainFollower::new(&client, 10).from_height(1);
+        assert_eq!(follower.poll().unwrap().len(), 3);
+
+        // Replace block 3 by a fork that is one block longer.
+        {
+            let mut blocks = blocks.lock().unwrap();
+            blocks.pop();
+            blocks.push(block(3, &hash(103), &hash(2)));
+            blocks.push(block(4, &hash(104), &hash(103)));
+        }
+
+        match &follower.poll().unwrap()[..] {
+            [follower::ChainEvent::Reverted(reverted), follower::ChainEvent::Applied(three), follower::ChainEvent::Applied(four)] =>
+            {
+                assert_eq!(reverted.hash, hash(3));
+                assert_eq!(three.hash, hash(103));
+                assert_eq!(four.hash, hash(104));
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert_eq!(follower.tip().unwrap().hash, hash(104));
+    }
+
+    #[test]
+    fn chain_follower_replaced_tip() {
+        let server = MockServer::start();
+        let blocks = chain(&server, linear_chain(1, 3, 0));
+        let client = server.client();
+        let mut follower = follower::ChainFollower::new(&client, 10).from_height(1);
+        assert_eq!(follower.poll().unwrap().len(), 3);
+        assert!(follower.poll().unwrap().is_empty());
+
+        // Block 3 is replaced without the head moving.
+        blocks.lock().unwrap()[2] = block(3, &hash(103), &hash(2));
+        match &follower.poll().unwrap()[..] {
+            [follower::ChainEvent::Reverted(reverted), follower::ChainEvent::Applied(applied)] => {
+                assert_eq!(reverted.hash, hash(3));
+                assert_eq!(applied.hash, hash(103));
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+
+        // The node switches to a shorter chain.
+        *blocks.lock().unwrap() = linear_chain(1, 1, 0)
+            .into_iter()
+            .chain(linear_chain(2, 2, 200))
+            .collect();
+        match &follower.poll().unwrap()[..] {
+            [follower::ChainEvent::Reverted(three), follower::ChainEvent::Reverted(two), follower::ChainEvent::Applied(applied)] =>
+            {
+                assert_eq!(three.hash, hash(103));
+                assert_eq!(two.hash, hash(2));
+                assert_eq!(applied.hash, hash(202));
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert_eq!(follower.tip().unwrap().hash, hash(202));
+    }
+
+    #[test]
+    fn chain_follower_deep_reorg() {
+        let server = MockServer::start();
+        let blocks = chain(&server, linear_chain(1, 3, 0));
+        let client = server.client();
+
+        // Every block is final right away, so replacing the tip reverts a finalized block.
+        let mut follower = follower::ChainFollower::new(&client, 1).from_height(1);
+        assert_eq!(follower.poll().unwrap().len(), 6);
+        {
+            let mut blocks = blocks.lock().unwrap();
+            blocks.pop();
+            blocks.push(block(3, &hash(103), &hash(2)));
+            blocks.push(block(4, &hash(104), &hash(103)));
+        }
+        let err = follower.poll().unwrap_err();
+        let reorg = follower::DeepReorg::from_error(&err).unwrap();
+        assert_eq!((reorg.number, reorg.hash.as_str()), (3, hash(3).as_str()));
+        assert!(!transport::is_transport_error(&err));
+        assert_eq!(follower.tip().unwrap().hash, hash(3));
+        assert!(follower.poll().is_err());
+
+        // Walking back stops at the finalized block instead of accepting the fork below it.
+        *blocks.lock().unwrap() = linear_chain(1, 3, 0);
+        let mut follower = follower::ChainFollower::new(&client, 2).from_height(1);
+        follower.poll().unwrap();
+        *blocks.lock().unwrap() = linear_chain(1, 1, 0)
+            .into_iter()
+            .chain(linear_chain(2, 4, 100))
+            .collect();
+        let err = follower.poll().unwrap_err();
+        assert_eq!(follower::DeepReorg::from_error(&err).unwrap().number, 2);
+        // The failed poll did not revert block 3.
+        assert_eq!(follower.tip().unwrap().hash, hash(3));
+    }
+
+    #[test]
+    fn chain_follower_finalizes() {
+        let server = MockServer::start();
+        chain(&server, linear_chain(1, 5, 0));
+        let client = server.client();
+        let mut follower = follower::ChainFollower::new(&client, 3).from_height(1);
+        let finalized: Vec<u64> = follower
+            .poll()
+            .unwrap()
+            .into_iter()
+            .filter_map(|event| match event {
+                follower::ChainEvent::Finalized(block) => Some(block.number),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finalized, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn address_watcher() {
+        let server = MockServer::start();
+        let mut blocks = linear_chain(1, 3, 0);
+        blocks[1]["transactions"] =
+            json!([transaction(TX_HASH, &blocks[1], 0, OTHER_ADDRESS, ADDRESS)]);
+        let pending = pending(TX_HASH, OTHER_ADDRESS, ADDRESS);
+        let head = chain(&server, blocks[..1].to_vec());
+        server.on("mempoolContent", json!([pending]));
+
+        let client = server.client();
+        let mut watcher = watcher::AddressWatcher::new(&client, 2).from_height(1);
+        watcher.watch(ADDRESS);
+
+        let events = watcher.poll().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, watcher::PaymentStatus::Pending);
+        assert_eq!(events[0].direction, watcher::Direction::Incoming);
+
+        server.on("mempoolContent", json!([]));
+        *head.lock().unwrap() = blocks;
+        let statuses: Vec<watcher::PaymentStatus> = watcher
+            .poll()
+            .unwrap()
+            .into_iter()
+            .map(|event| event.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                watcher::PaymentStatus::Confirmed(1),
+                watcher::PaymentStatus::Confirmed(2),
+                watcher::PaymentStatus::Finalized
+            ]
+        );
+    }
+
+    #[test]
+    fn address_watcher_mempool_failure() {
+        let server = MockServer::start();
+        let mut blocks = linear_chain(1, 2, 0);
+        blocks[1]["transactions"] =
+            json!([transaction(TX_HASH, &blocks[1], 0, OTHER_ADDRESS, ADDRESS)]);
+        let head = chain(&server, blocks[..1].to_vec());
+        server.on("mempoolContent", json!([]));
+
+        let client = server.client();
+        let mut watcher = watcher::AddressWatcher::new(&client, 2).from_height(1);
+        watcher.watch(ADDRESS);
+        assert!(watcher.poll().unwrap().is_empty());
+
+        // The poll fails after the block with the payment arrived, it is reported next time.
+        server.on_error("mempoolContent", -32603, "Internal error");
+        *head.lock().unwrap() = blocks;
+        assert!(watcher.poll().is_err());
+        server.on("mempoolContent", json!([]));
+        let events = watcher.poll().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].transaction.hash, TX_HASH);
+        assert_eq!(events[0].status, watcher::PaymentStatus::Confirmed(1));
+    }
+
+    #[test]
+    fn address_watcher_reorg() {
+        let statuses = |watcher: &mut watcher::AddressWatcher| -> Vec<watcher::PaymentStatus> {
+            watcher
+                .poll()
+                .unwrap()
+                .into_iter()
+                .map(|event| event.status)
+                .collect()
+        };
+        let mut blocks = linear_chain(1, 2, 0);
+        blocks[1]["transactions"] =
+            json!([transaction(TX_HASH, &blocks[1], 0, OTHER_ADDRESS, ADDRESS)]);
+        let mut fork = linear_chain(1, 1, 0);
+        fork.extend(linear_chain(2, 3, 100));
+
+        // With one confirmation the payment is final at once, the reorg fails the poll.
+        let server = MockServer::start();
+        server.on("mempoolContent", json!([]));
+        let head = chain(&server, blocks.clone());
+        let client = server.client();
+        let mut watcher = watcher::AddressWatcher::new(&client, 1).from_height(1);
+        watcher.watch(ADDRESS);
+        assert_eq!(
+            statuses(&mut watcher),
+            vec![
+                watcher::PaymentStatus::Confirmed(1),
+                watcher::PaymentStatus::Finalized
+            ]
+        );
+        *head.lock().unwrap() = fork.clone();
+        let err = watcher.poll().unwrap_err();
+        assert_eq!(follower::DeepReorg::from_error(&err).unwrap().number, 2);
+
+        // With two confirmations the payment is still reverted.
+        let server = MockServer::start();
+        server.on("mempoolContent", json!([]));
+        let head = chain(&server, blocks);
+        let client = server.client();
+        let mut watcher = watcher::AddressWatcher::new(&client, 2).from_height(1);
+        watcher.watch(ADDRESS);
+        assert_eq!(
+            statuses(&mut watcher),
+            vec![watcher::PaymentStatus::Confirmed(1)]
+        );
+        *head.lock().unwrap() = fork;
+        assert_eq!(
+            statuses(&mut watcher),
+            vec![watcher::PaymentStatus::Reverted]
+        );
+    }
+
+    #[test]
+    fn transaction_history() {
+        let server = MockServer::start();
+        let mut blocks = linear_chain(1, 4, 0);
+        for (number, block) in blocks.iter_mut().enumerate() {
+            block["transactions"] = json!([transaction(
+                &hash(1000 + number as u64),
+                block,
+                0,
+                ADDRESS,
+                OTHER_ADDRESS
+            )]);
+        }
+        let latest: Vec<Value> = blocks[2..]
+            .iter()
+            .rev()
+            .map(|block| block["transactions"][0].clone())
+            .collect();
+        chain(&server, blocks);
+        server.on("getTransactionsByAddress", json!(latest));
+
+        let client = server.client();
+        let hashes: Vec<String> = history::TransactionHistory::new(&client, ADDRESS)
+            .page_size(2)
+            .map(|tx| tx.unwrap().hash)
+            .collect();
+        assert_eq!(hashes, vec![hash(1000), hash(1001), hash(1002), hash(1003)]);
+        assert_eq!(server.requests_for("getBlockByNumber").len(), 3);
+    }
+
+    #[test]
+    fn fee_estimator() {
+        let server = MockServer::start();
+        let mut blocks = linear_chain(1, 2, 0);
+        blocks[1]["size"] = json!(99000);
+        blocks[1]["transactions"] =
+            json!([transaction(TX_HASH, &blocks[1], 0, ADDRESS, OTHER_ADDRESS)]);
+        chain(&server, blocks);
+        server.on("minFeePerByte", 0);
+        server.on("mempool", json!({"total": 0, "buckets": []}));
+
+        let client = server.client();
+        let estimate = fee::FeeEstimator::new(&client)
+            .sample_blocks(2)
+            .estimate()
+            .unwrap();
+        assert_eq!(estimate.next_block, 1.0);
+        assert!(estimate.next_block >= estimate.within_five_blocks);
+        assert!(estimate.within_five_blocks >= estimate.economy);
+        assert_eq!(
+            estimate.total_fee(fee::FeePriority::NextBlock, fee::BASIC_TRANSACTION_SIZE),
+            138
+        );
+    }
+
+    #[cfg(feature = "indexer")]
+    #[test]
+    fn indexer() {
+        let server = MockServer::start();
+        let mut blocks = linear_chain(1, 3, 0);
+        blocks[1]["transactions"] =
+            json!([transaction(TX_HASH, &blocks[1], 0, ADDRESS, OTHER_ADDRESS)]);
+        let chain = chain(&server, blocks);
+
+        let client = server.client();
+        let mut indexer = indexer::Indexer::open_in_memory(&client).unwrap();
+        assert_eq!(indexer.sync().unwrap(), 3);
+        assert_eq!(
+            indexer.transactions_by_address(ADDRESS, 10).unwrap()[0].hash,
+            TX_HASH
+        );
+        assert_eq!(
+            indexer
+                .transaction_receipt(TX_HASH)
+                .unwrap()
+                .unwrap()
+                .confirmations,
+            2
+        );
+
+        // Fork off after block 1, dropping the transaction in block 2.
+        {
+            let mut blocks = chain.lock().unwrap();
+            blocks.truncate(1);
+            blocks.extend(linear_chain(2, 4, 100));
+        }
+        assert_eq!(indexer.sync().unwrap(), 4);
+        assert!(indexer.transaction_by_hash(TX_HASH).unwrap().is_none());
+        assert_eq!(indexer.block_by_number(2).unwrap().unwrap().hash, hash(102));
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let server = MockServer::start();
+        server.on("blockNumber", 882418);
+        server.on("getBlockByNumber", block(882418, BLOCK_HASH, &hash(882417)));
+
+        let cassette = std::env::temp_dir().join(format!("nimiq-rpc-{}.json", std::process::id()));
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let client = Client::new_with_transport(RecordingTransport::new(agent, &cassette));
+        client.block_number().unwrap();
+        client.get_block_by_number(882418, false).unwrap();
+        drop(server);
+
+        let client = Client::new_with_transport(ReplayTransport::open(&cassette).unwrap());
+        assert_eq!(client.block_number().unwrap(), 882418);
+        assert_eq!(
+            client.get_block_by_number(882418, false).unwrap().hash,
+            BLOCK_HASH
+        );
+        assert_eq!(
+            client.get_block_by_number(882418, false).unwrap().hash,
+            BLOCK_HASH
+        );
+        // Recorded blocks are not answered for other parameters.
+        assert!(client.get_block_by_number(1, false).is_err());
+        assert_eq!(client.supports("getBlockByNumber"), Some(true));
+        assert!(client.consensus().is_err());
+        assert_eq!(client.supports("consensus"), None);
+
+        let replay = Arc::new(ReplayTransport::open(&cassette).unwrap().strict(true));
+        let client = Client::new_with_transport(Arc::clone(&replay));
+        assert!(client.get_block_by_number(1, false).is_err());
+        client.block_number().unwrap();
+        assert!(client.block_number().is_err());
+        assert_eq!(replay.remaining()[0].method, "getBlockByNumber");
+        std::fs::remove_file(cassette).unwrap();
+    }
+
+    #[test]
+    fn simulated_chain() {
+        const RECIPIENT: &str = "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2";
+        let chain = SimulatedChain::new();
+        chain.set_balance(ADDRESS, 1000);
+        let client = chain.client();
+
+        let tx = primitives::OutgoingTransaction {
+            from: ADDRESS,
+            to: RECIPIENT,
+            value: 500,
+            fee: 138,
+        };
+        let first = client.send_transaction(&tx).unwrap();
+        assert!(client.send_transaction(&tx).is_err());
+        assert!(client.get_transaction_receipt(&first).is_err());
+        assert_eq!(client.mempool_content().unwrap(), vec![first.clone()]);
+
+        let block = chain.mine_block();
+        assert_eq!(client.block_number().unwrap(), 2);
+        assert_eq!(client.get_balance(ADDRESS).unwrap(), 362);
+        assert_eq!(client.get_balance(RECIPIENT).unwrap(), 500);
+        assert!(chain.mempool().is_empty());
+
+        let receipt = client.get_transaction_receipt(&first).unwrap();
+        assert_eq!(receipt.block_hash, block.hash);
+        assert_eq!(receipt.confirmations, 1);
+
+        let raw = client
+            .create_raw_transaction(&primitives::OutgoingTransaction {
+                from: RECIPIENT,
+                to: ADDRESS,
+                value: 100,
+                fee: 0,
+            })
+            .unwrap();
+        client.send_raw_transaction(&raw).unwrap();
+        chain.mine_block();
+        assert_eq!(
+            client
+                .get_transaction_receipt(&first)
+                .unwrap()
+                .confirmations,
+            2
+        );
+        assert_eq!(client.get_balance(ADDRESS).unwrap(), 462);
+
+        let history = client.get_transactions_by_address(RECIPIENT, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].to_address, ADDRESS);
+        assert_eq!(history[1].hash, first);
+    }
+
+    #[test]
+    fn simulated_chain_balance_overflow() {
+        const RECIPIENT: &str = "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2";
+        let chain = SimulatedChain::new();
+        chain.set_balance(ADDRESS, 1000);
+        let client = chain.client();
+
+        let huge = primitives::OutgoingTransaction {
+            from: ADDRESS,
+            to: RECIPIENT,
+            value: u64::MAX,
+            fee: 1,
+        };
+        match client.send_transaction(&huge) {
+            Err(jsonrpc::error::Error::Rpc(err)) => assert_eq!(err.message, "Insufficient funds"),
+            result => panic!("expected an RPC error, got {:?}", result),
+        }
+
+        client
+            .send_transaction(&primitives::OutgoingTransaction {
+                from: ADDRESS,
+                to: RECIPIENT,
+                value: 500,
+                fee: 0,
+            })
+            .unwrap();
+        chain.mine_block();
+        // Reverting a payment the recipient no longer has leaves it with nothing.
+        chain.set_balance(RECIPIENT, 100);
+        chain.revert(1);
+        assert_eq!(client.get_balance(RECIPIENT).unwrap(), 0);
+        assert_eq!(client.get_balance(ADDRESS).unwrap(), 1000);
+        assert_eq!(client.block_number().unwrap(), 1);
+    }
+
+    #[test]
+    fn simulated_chain_reorg() {
+        let chain = SimulatedChain::new();
+        chain.set_balance(ADDRESS, 1000);
+        let client = chain.client();
+        chain.mine_empty_block();
+
+        let mut follower = follower::ChainFollower::new(&client, 10).from_height(1);
+        let mut watcher = watcher::AddressWatcher::new(&client, 10).from_height(1);
+        watcher.watch(ADDRESS);
+        assert_eq!(follower.poll().unwrap().len(), 2);
+
+        let hash = client
+            .send_transaction(&primitives::OutgoingTransaction {
+                from: ADDRESS,
+                to: "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2",
+                value: 500,
+                fee: 0,
+            })
+            .unwrap();
+        let mined = chain.mine_block();
+        assert_eq!(follower.poll().unwrap().len(), 1);
+        assert!(matches!(
+            watcher.poll().unwrap()[..],
+            [watcher::PaymentEvent {
+                status: watcher::PaymentStatus::Confirmed(1),
+                ..
+            }]
+        ));
+
+        chain.reorg(1);
+        assert_eq!(client.get_balance(ADDRESS).unwrap(), 1000);
+        assert_eq!(chain.mempool()[0].hash, hash);
+        match &follower.poll().unwrap()[..] {
+            [follower::ChainEvent::Reverted(reverted), follower::ChainEvent::Applied(_), follower::ChainEvent::Applied(head)] =>
+            {
+                assert_eq!(reverted.hash, mined.hash);
+                assert_eq!(head.number, 4);
+            }
+            events => panic!("unexpected events {:?}", events),
         }
+        assert!(watcher
+            .poll()
+            .unwrap()
+            .iter()
+            .any(|event| event.status == watcher::PaymentStatus::Reverted));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn cli() {
+        use std::process::Command;
+
+        let server = MockServer::start();
+        server
+            .on("getBalance", 150000)
+            .on("getAccount", json!({"id": "ad25610feb43d75307763d3f010822a757027429", "address": ADDRESS, "balance": 1234567, "type": 0}))
+            .on("sendTransaction", TX_HASH);
+        let run = |args: &[&str]| {
+            let output = Command::new(env!("CARGO_BIN_EXE_nimiq-rpc"))
+                .args(args)
+                .env("NIMIQ_RPC_HOST", server.url())
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        };
+
+        assert_eq!(run(&["get-balance", ADDRESS]), "1.50000 NIM\n");
+        assert_eq!(run(&["--luna", "get-balance", ADDRESS]), "150000\n");
+        assert!(run(&["get-account", ADDRESS]).contains("balance  12.34567 NIM"));
+        let account: Value =
+            serde_json::from_str(&run(&["-o", "json", "get-account", ADDRESS])).unwrap();
+        assert_eq!(account["balance"], "12.34567 NIM");
+
+        assert_eq!(
+            run(&["send-transaction", ADDRESS, OTHER_ADDRESS, "1.5", "0.00138"]).trim(),
+            TX_HASH
+        );
+        assert_eq!(
+            server.requests_for("sendTransaction")[0].params[0],
+            json!({"from": ADDRESS, "to": OTHER_ADDRESS, "value": 150000, "fee": 138})
+        );
+
+        let output = Command::new(env!("CARGO_BIN_EXE_nimiq-rpc"))
+            .args(["send-transaction", ADDRESS, OTHER_ADDRESS, "1.5", "50000"])
+            .env("NIMIQ_RPC_HOST", server.url())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "error: fee is too large\n"
+        );
+        assert_eq!(server.requests_for("sendTransaction").len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn shell() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let server = MockServer::start();
+        server
+            .on("blockNumber", 882418)
+            .on("accounts", json!([{"id": "ad25610feb43d75307763d3f010822a757027429", "address": ADDRESS, "balance": 0, "type": 0}]))
+            .on_call("getBalance", |params| {
+                assert_eq!(params[0], ADDRESS);
+                Ok(json!(250000))
+            });
+        let history =
+            std::env::temp_dir().join(format!("nimiq-rpc-history-{}", std::process::id()));
+
+        let mut shell = Command::new(env!("CARGO_BIN_EXE_nimiq-rpc"))
+            .arg("shell")
+            .env("NIMIQ_RPC_HOST", server.url())
+            .env("NIMIQ_RPC_HISTORY", &history)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        shell
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"block_number\naccounts | get-balance $.address\nexit\n")
+            .unwrap();
+        let output = shell.wait_with_output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("882418\n"));
+        assert!(stdout.contains("2.50000 NIM\n"));
+
+        assert!(std::fs::read_to_string(&history)
+            .unwrap()
+            .contains("block_number"));
+        std::fs::remove_file(history).unwrap();
+    }
+
+    #[test]
+    fn exporter() {
+        use std::io::{Read, Write};
+
+        let server = MockServer::start();
+        server
+            .on("blockNumber", 882418)
+            .on("consensus", "established")
+            .on("syncing", false)
+            .on("peerCount", 2)
+            .on(
+                "peerList",
+                json!([
+                    {"id": "b99034c552e9c0fd34eb95c1cdf17f5e", "address": "wss://seed1.nimiq-testnet.com:8080/b99034c552e9c0fd34eb95c1cdf17f5e", "addressState": 2, "connectionState": 5, "version": 2, "timeOffset": 186, "headHash": BLOCK_HASH, "latency": 62, "rx": 3440, "tx": 2696},
+                    {"id": "e37dca72802c972d45b37735e9595cf0", "address": "wss://seed4.nimiq-testnet.com:8080/e37dca72802c972d45b37735e9595cf0", "addressState": 4}
+                ]),
+            )
+            .on("mining", false)
+            .on("mempool", json!({"total": 3, "buckets": [1], "1": 3}))
+            .on_error("hashrate", -32603, "Internal error");
+
+        let exporter = exporter::Exporter::new(server.client()).timeout(Duration::from_millis(100));
+        let metrics = exporter.collect();
+        assert!(metrics.contains("# TYPE nimiq_block_number gauge\nnimiq_block_number 882418\n"));
+        assert!(metrics.contains("nimiq_up 1\n"));
+        assert!(metrics.contains("nimiq_consensus_established 1\n"));
+        assert!(metrics.contains("nimiq_syncing 0\n"));
+        assert!(metrics.contains("nimiq_peer_count 2\n"));
+        assert!(metrics.contains("nimiq_peer_latency_milliseconds{peer=\"wss://seed1.nimiq-testnet.com:8080/b99034c552e9c0fd34eb95c1cdf17f5e\"} 62\n"));
+        assert!(metrics.contains("nimiq_peer_received_bytes_total{peer=\"wss://seed1.nimiq-testnet.com:8080/b99034c552e9c0fd34eb95c1cdf17f5e\"} 3440\n"));
+        assert!(!metrics.contains("seed4"));
+        assert!(metrics.contains("nimiq_mempool_transactions 3\n"));
+        assert!(!metrics.contains("nimiq_hashrate"));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || exporter.serve(listener));
+
+        // A client that never sends a request does not block the next scrape.
+        let _idle = std::net::TcpStream::connect(address).unwrap();
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("nimiq_block_number 882418\n"));
+    }
+
+    #[test]
+    fn request_metrics() {
+        let server = MockServer::start();
+        server
+            .on("blockNumber", 882418)
+            .on_error("getTransactionsByAddress", -32603, "Internal error")
+            .latency("getTransactionsByAddress", Duration::from_millis(30));
+        let client = server.client();
+        client.block_number().unwrap();
+        client.block_number().unwrap();
+        assert!(client.get_transactions_by_address(ADDRESS, 10).is_err());
+
+        let metrics = client.metrics().snapshot();
+        assert_eq!(metrics["blockNumber"].calls, 2);
+        assert_eq!(metrics["blockNumber"].errors, 0);
+        let history = &metrics["getTransactionsByAddress"];
+        assert_eq!((history.calls, history.errors), (1, 1));
+        assert!(history.latency.mean() >= 0.03);
+        assert_eq!(history.latency.buckets[..3].iter().sum::<u64>(), 0);
+        assert_eq!(history.latency.buckets.iter().sum::<u64>(), 1);
+
+        let prometheus = client.metrics().to_prometheus("nimiq");
+        assert!(prometheus.contains("nimiq_rpc_requests_total{method=\"blockNumber\"} 2\n"));
+        assert!(
+            prometheus.contains("nimiq_rpc_errors_total{method=\"getTransactionsByAddress\"} 1\n")
+        );
+        assert!(prometheus.contains(
+            "nimiq_rpc_request_duration_seconds_bucket{method=\"getTransactionsByAddress\",le=\"0.025\"} 0\n"
+        ));
+        assert!(prometheus.contains(
+            "nimiq_rpc_request_duration_seconds_count{method=\"getTransactionsByAddress\"} 1\n"
+        ));
+
+        client.metrics().reset();
+        assert!(client.metrics().snapshot().is_empty());
+    }
+
+    #[cfg(feature = "mining")]
+    fn work(target: u32) -> Value {
+        let mut header = vec![0u8; 146];
+        header[1] = 1;
+        header[130..134].copy_from_slice(&target.to_be_bytes());
+        header[134..138].copy_from_slice(&882419u32.to_be_bytes());
+        json!({
+            "data": hex::encode(header),
+            "suffix": "0000",
+            "target": target,
+            "algorithm": "nimiq-argon2"
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "mining")]
+    fn miner() {
+        let server = MockServer::start();
+        server
+            .on("getWork", work(0x2100ffff))
+            .on("blockNumber", 882418)
+            .on("submitBlock", Value::Null);
+        let client = server.client();
+        let miner = mining::Miner::new(&client).threads(2);
+
+        let mined = miner.mine_block().unwrap().unwrap();
+        assert_eq!(mined.height, 882419);
+        assert_eq!(miner.blocks_found(), 1);
+        assert!(miner.hashes() >= 1);
+        assert_eq!(server.requests_for("submitBlock")[0].params[0], mined.block);
+
+        let block = hex::decode(&mined.block).unwrap();
+        assert_eq!(block.len(), 148);
+        assert_eq!(&block[142..146], &mined.nonce.to_be_bytes());
+        let hash = pow::hash(&block[..146]);
+        assert_eq!(hex::encode(hash), mined.pow);
+        assert!(pow::meets_target(
+            &hash,
+            &pow::compact_to_target(0x2100ffff)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "mining")]
+    fn miner_invalid_work() {
+        let server = MockServer::start();
+        let mut work = work(0x2100ffff);
+        work["data"] = json!("00");
+        server.on("getWork", work).on("blockNumber", 882418);
+        let client = server.client();
+
+        let err = mining::Miner::new(&client).mine_block().unwrap_err();
+        let invalid = mining::InvalidWork::from_error(&err).unwrap();
+        assert_eq!(invalid.reason, "header has the wrong size");
+        assert!(!transport::is_transport_error(&err));
+        assert!(server.requests_for("submitBlock").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "mining")]
+    fn miner_abandons_stale_work() {
+        let server = MockServer::start();
+        server
+            .on("getWork", work(0))
+            .on("blockNumber", 882419)
+            .on("submitBlock", Value::Null);
+        let client = server.client();
+        let miner = mining::Miner::new(&client)
+            .threads(1)
+            .poll_interval(Duration::from_millis(10));
+
+        assert!(miner.mine_block().unwrap().is_none());
+        assert!(server.requests_for("submitBlock").is_empty());
+
+        let stop = miner.stop_handle();
+        stop.stop();
+        assert_eq!(miner.run().unwrap(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "pow")]
+    fn pow_targets() {
+        for compact in &[0x1f010000, 0x1d00ffff, 0x1e00ffff, 0x2100ffff] {
+            assert_eq!(
+                pow::target_to_compact(&pow::compact_to_target(*compact)),
+                *compact
+            );
+        }
+        let target = pow::compact_to_target(0x1e00ffff);
+        assert!((pow::target_to_difficulty(&target) - 256.0039063096).abs() < 1e-9);
+        assert_eq!(
+            pow::difficulty_to_target(1.0),
+            pow::compact_to_target(pow::BLOCK_TARGET_MAX_COMPACT)
+        );
+        assert_eq!(
+            pow::target_to_compact(&pow::difficulty_to_target(pow::target_to_difficulty(
+                &target
+            ))),
+            0x1e00ffff
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "pow")]
+    fn pow_verify_header() {
+        let server = MockServer::start();
+        server.on(
+            "getBlockTemplate",
+            json!({
+                "header": {
+                    "version": 1,
+                    "prevHash": BLOCK_HASH,
+                    "interlinkHash": hash(1),
+                    "accountsHash": hash(2),
+                    "nBits": 0x2100ffff,
+                    "height": 882419
+                },
+                "interlink": "0000",
+                "target": 0x2100ffff,
+                "body": {
+                    "hash": hash(3),
+                    "minerAddr": "ad25610feb43d75307763d3f010822a757027429",
+                    "extraData": "",
+                    "transactions": [],
+                    "merkleHashes": [],
+                    "prunedAccounts": []
+                }
+            }),
+        );
+        let template = server.client().get_block_template().unwrap();
+        let mut header = block::BlockHeader::from_template(&template, 1585049478, 0).unwrap();
+        assert_eq!(header.height, 882419);
+        assert_eq!(hex::encode(header.body_hash), hash(3));
+
+        while !pow::verify_header(&header) {
+            header.nonce += 1;
+        }
+        let bytes = header.serialize();
+        assert_eq!(bytes.len(), block::BlockHeader::SIZE);
+        assert_eq!(
+            block::BlockHeader::from_hex(&hex::encode(&bytes)).unwrap(),
+            header
+        );
+        assert_eq!(
+            block::BlockHeader::deserialize(&bytes[..100]),
+            Err(block::DecodeError::UnexpectedEnd)
+        );
+
+        header.n_bits = 0;
+        assert!(!pow::verify_header(&header));
+    }
+
+    #[test]
+    #[cfg(feature = "pow")]
+    fn pow_verify_block() {
+        let mut block: primitives::Block =
+            serde_json::from_value(block(882418, BLOCK_HASH, &hash(0))).unwrap();
+        assert_eq!(pow::verify_block(&block), Ok(true));
+        block.difficulty = "1e12".to_owned();
+        assert_eq!(pow::verify_block(&block), Ok(false));
+        block.difficulty = "0".to_owned();
+        assert!(pow::verify_block(&block).is_err());
+    }
+
+    #[test]
+    fn block_serialization() {
+        assert_eq!(
+            hex::encode(merkle::hash(b"")),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+
+        let basic = format!(
+            "00{}{}{:016x}{:016x}{:08x}2a{}",
+            "11".repeat(32),
+            "22".repeat(20),
+            100000,
+            138,
+            882000,
+            "33".repeat(64)
+        );
+        let content = format!(
+            "0002abcd{}00{}01{:016x}{:016x}{:08x}2a00",
+            "44".repeat(20),
+            "55".repeat(20),
+            500,
+            0,
+            882000
+        );
+        let extended = format!("01{}0003{}", content, "66".repeat(3));
+        let vesting = format!(
+            "{}01{:016x}{}{:08x}{:08x}{:016x}{:016x}",
+            "77".repeat(20),
+            0,
+            "88".repeat(20),
+            1,
+            100,
+            10,
+            1000
+        );
+
+        let server = MockServer::start();
+        server.on(
+            "getBlockTemplate",
+            json!({
+                "header": {
+                    "version": 1,
+                    "prevHash": hash(1),
+                    "interlinkHash": hash(2),
+                    "accountsHash": hash(3),
+                    "nBits": 0x1f010000,
+                    "height": 882419
+                },
+                "interlink": format!("03c0{}", hash(4)),
+                "target": 0x1f010000,
+                "body": {
+                    "hash": hash(5),
+                    "minerAddr": "ad25610feb43d75307763d3f010822a757027429",
+                    "extraData": "",
+                    "transactions": [basic, extended],
+                    "merkleHashes": [],
+                    "prunedAccounts": [vesting]
+                }
+            }),
+        );
+        let template = server.client().get_block_template().unwrap();
+        let mut raw = block::RawBlock::from_template(&template, 1585049478, 7).unwrap();
+        let first = hex::decode(hash(1)).unwrap();
+        let last = hex::decode(hash(4)).unwrap();
+        assert_eq!(raw.interlink.hashes.len(), 3);
+        assert_eq!(raw.interlink.hashes[0][..], first[..]);
+        assert_eq!(raw.interlink.hashes[1][..], first[..]);
+        assert_eq!(raw.interlink.hashes[2][..], last[..]);
+        assert_eq!(hex::encode(raw.header.body_hash), hash(5));
+
+        let bytes = raw.serialize();
+        assert_eq!(
+            &bytes[..block::BlockHeader::SIZE],
+            &raw.header.serialize()[..]
+        );
+        assert_eq!(
+            hex::encode(&bytes[block::BlockHeader::SIZE..block::BlockHeader::SIZE + 34]),
+            format!("03c0{}", hash(4))
+        );
+        assert_eq!(block::RawBlock::from_hex(&raw.to_hex()).unwrap(), raw);
+        assert_eq!(raw.hash(), merkle::hash(&raw.header.serialize()));
+
+        raw.set_extra_data(b"pool".to_vec()).unwrap();
+        let body = raw.body.clone().unwrap();
+        assert_eq!(body.extra_data, b"pool");
+        assert_eq!(raw.header.body_hash, body.hash().unwrap());
+        assert_ne!(hex::encode(raw.header.body_hash), hash(5));
+        assert_eq!(block::RawBlock::from_hex(&raw.to_hex()).unwrap(), raw);
+        assert!(raw.set_extra_data(vec![0; 256]).is_err());
+
+        // Only the extended transaction's content is hashed as is.
+        let extended_body = block::BlockBody {
+            transactions: vec![hex::decode(&extended).unwrap()],
+            ..body.clone()
+        };
+        let leaves = [
+            merkle::hash(&body.miner_addr),
+            merkle::hash(b"pool"),
+            merkle::hash(&hex::decode(&content).unwrap()),
+            merkle::hash(&hex::decode(&vesting).unwrap()),
+        ];
+        assert_eq!(extended_body.hash().unwrap(), merkle::root(&leaves));
+
+        let mut light = raw.clone();
+        light.body = None;
+        assert_eq!(block::RawBlock::from_hex(&light.to_hex()).unwrap(), light);
+        assert_eq!(
+            block::RawBlock::deserialize(&raw.serialize()[..200]),
+            Err(block::DecodeError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn merkle_verify_block() {
+        let leaf = |seed| hex::decode(hash(seed)).unwrap().try_into().unwrap();
+        let miner: [u8; 20] = hex::decode("f2d9e3a3c7cc9e3b4d6a56dcf8b9d6e6c8f1e5e2")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let body_hash = merkle::body_hash(&miner, b"", &[leaf(7), leaf(8)], &[]);
+
+        let mut value = block(882418, BLOCK_HASH, &hash(0));
+        value["bodyHash"] = json!(hex::encode(body_hash));
+        value["transactions"] = json!([
+            transaction(&hash(7), &value, 0, ADDRESS, OTHER_ADDRESS),
+            transaction(&hash(8), &value, 1, ADDRESS, OTHER_ADDRESS)
+        ]);
+        let server = MockServer::start();
+        server.on("getBlockByHash", value.clone());
+        let full = server.client().get_block_by_hash(BLOCK_HASH, true).unwrap();
+        assert_eq!(merkle::verify_block(&full, &[]), Ok(true));
+
+        value["transactions"] = json!([hash(7), hash(8)]);
+        let hashes: primitives::Block = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(merkle::verify_block(&hashes, &[]), Ok(true));
+        // A pruned account the node did not report changes the hash.
+        assert_eq!(merkle::verify_block(&hashes, &[vec![0; 29]]), Ok(false));
+
+        value["transactions"] = json!([hash(8), hash(7)]);
+        let reordered: primitives::Block = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(merkle::verify_block(&reordered, &[]), Ok(false));
+        value["transactions"] = json!([hash(7)]);
+        let missing: primitives::Block = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(merkle::verify_block(&missing, &[]), Ok(false));
+        value["miner"] = json!("f2d9");
+        let invalid: primitives::Block = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            merkle::verify_block(&invalid, &[]),
+            Err(block::DecodeError::InvalidField("miner"))
+        );
+    }
+
+    #[test]
+    fn merkle_verify_body() {
+        let extended = |value: u64| {
+            format!(
+                "010000{}00{}00{:016x}{:016x}{:08x}2a000000",
+                "44".repeat(20),
+                "55".repeat(20),
+                value,
+                0,
+                882000
+            )
+        };
+        let transactions = vec![extended(1), extended(2)];
+        let mut template = block::BlockBody {
+            miner_addr: [0xad; 20],
+            extra_data: b"pool".to_vec(),
+            transactions: transactions
+                .iter()
+                .map(|tx| hex::decode(tx).unwrap())
+                .collect(),
+            pruned_accounts: vec![],
+        };
+        let transaction_hashes: Vec<_> = template
+            .transactions
+            .iter()
+            .map(|tx| merkle::hash(&tx[1..tx.len() - 2]))
+            .collect();
+        let mut body = primitives::Body {
+            hash: hex::encode(template.hash().unwrap()),
+            miner_addr: hex::encode(template.miner_addr),
+            extra_data: hex::encode(&template.extra_data),
+            transactions,
+            merkle_hashes: vec![hex::encode(merkle::root(&transaction_hashes))],
+            pruned_accounts: vec![],
+        };
+        assert_eq!(merkle::verify_body(&body), Ok(true));
+
+        // The path lets the miner address and extra data change without the transactions.
+        template.extra_data = b"other pool".to_vec();
+        assert_eq!(
+            merkle::body_hash_from_path(
+                &template.miner_addr,
+                &template.extra_data,
+                &[merkle::root(&transaction_hashes)]
+            ),
+            template.hash().unwrap()
+        );
+
+        body.merkle_hashes = vec![hash(1)];
+        assert_eq!(merkle::verify_body(&body), Ok(false));
+        body.merkle_hashes.clear();
+        assert_eq!(merkle::verify_body(&body), Ok(true));
+        body.transactions.pop();
+        assert_eq!(merkle::verify_body(&body), Ok(false));
+    }
+
+    #[test]
+    fn superblock_levels() {
+        let pow: [u8; 32] = hex::decode(block(1, BLOCK_HASH, &hash(0))["pow"].as_str().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        // 0x00000000_00d5... needs 216 bits.
+        assert_eq!(superblock::hash_depth(&pow), 24);
+        assert_eq!(superblock::difficulty_depth(58290.86017846), 15);
+        let mut target = [0; 32];
+        target[4] = 1;
+        assert_eq!(superblock::target_depth(&target), 24);
+        assert_eq!(superblock::level(&pow, &target), 0);
+        target[4] = 0;
+        target[6] = 0xff;
+        assert_eq!(superblock::target_depth(&target), 32);
+
+        let server = MockServer::start();
+        server.on("getBlockByNumber", block(1, BLOCK_HASH, &hash(0)));
+        let head = server.client().get_block_by_number(1, false).unwrap();
+        assert_eq!(superblock::block_level(&head), Ok(9));
+        let mut easy = head.clone();
+        easy.difficulty = "1e30".to_owned();
+        assert_eq!(superblock::block_level(&easy), Ok(0));
+        easy.difficulty = "0.5".to_owned();
+        assert!(superblock::block_level(&easy).is_err());
+    }
+
+    #[test]
+    fn interlink_hash() {
+        let leaf = |seed| -> [u8; 32] { hex::decode(hash(seed)).unwrap().try_into().unwrap() };
+        let prev_hash = leaf(1);
+        let interlink = block::BlockInterlink {
+            hashes: vec![prev_hash, prev_hash, leaf(2), leaf(2), leaf(3)],
+        };
+        let bytes = interlink.serialize(&prev_hash);
+        assert_eq!(hex::encode(&bytes), format!("05d0{}{}", hash(2), hash(3)));
+        assert_eq!(
+            block::BlockInterlink::deserialize(&bytes, &prev_hash).unwrap(),
+            interlink
+        );
+
+        let genesis = block::MAINNET_GENESIS_HASH;
+        let leaves = [merkle::hash(&[0xd0]), genesis, leaf(2), leaf(3)];
+        let interlink_hash = interlink.hash(&prev_hash, &genesis);
+        assert_eq!(interlink_hash, merkle::root(&leaves));
+        assert_ne!(interlink_hash, interlink.hash(&leaf(2), &genesis));
+
+        let raw = block::RawBlock {
+            header: block::BlockHeader {
+                prev_hash,
+                interlink_hash,
+                ..block::BlockHeader::default()
+            },
+            interlink: interlink.clone(),
+            body: None,
+        };
+        assert!(raw.verify_interlink(&genesis));
+        assert!(!raw.verify_interlink(&leaf(9)));
+
+        // A block one level above its target takes the two lowest positions.
+        let mut target = [0; 32];
+        target[4] = 0x80;
+        let mut pow = [0; 32];
+        pow[4] = 0x40;
+        let this = leaf(4);
+        let next = interlink.next(&this, &pow, &target, &target);
+        assert_eq!(next.hashes, vec![this, this, leaf(2), leaf(2), leaf(3)]);
+        // A harder next target drops the lowest level.
+        let mut harder = [0; 32];
+        harder[4] = 0x40;
+        let next = interlink.next(&this, &pow, &target, &harder);
+        assert_eq!(next.hashes, vec![this, leaf(2), leaf(2), leaf(3)]);
+        // A pow that only meets an easier target leaves this block out.
+        let mut easier_pow = [0; 32];
+        easier_pow[4] = 0xff;
+        let next = interlink.next(&this, &easier_pow, &target, &harder);
+        assert_eq!(next.hashes, vec![prev_hash, leaf(2), leaf(2), leaf(3)]);
+    }
+
+    fn albatross_block(number: u32, kind: Value) -> Value {
+        let mut block = json!({
+            "hash": hash(u64::from(number)),
+            "size": 538,
+            "batch": number / 60,
+            "epoch": number / 43200,
+            "network": "MainAlbatross",
+            "version": 1,
+            "number": number,
+            "timestamp": 1704067200000u64,
+            "parentHash": hash(u64::from(number) - 1),
+            "seed": "00".repeat(96),
+            "extraData": [],
+            "stateHash": hash(1),
+            "bodyHash": hash(2),
+            "historyHash": hash(3)
+        });
+        block
+            .as_object_mut()
+            .unwrap()
+            .extend(kind.as_object().unwrap().clone());
+        block
+    }
+
+    fn albatross_transaction(hash: &str, block_number: u32) -> Value {
+        json!({
+            "hash": hash,
+            "blockNumber": block_number,
+            "timestamp": 1704067200000u64,
+            "confirmations": 1,
+            "size": 138,
+            "relatedAddresses": [ADDRESS, "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2"],
+            "from": ADDRESS,
+            "fromType": 0,
+            "to": "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2",
+            "toType": 0,
+            "value": 100000,
+            "fee": 0,
+            "senderData": "",
+            "recipientData": "",
+            "flags": 0,
+            "validityStartHeight": block_number - 1,
+            "proof": "00",
+            "networkId": 24,
+            "executionResult": true
+        })
+    }
+
+    #[test]
+    fn albatross() {
+        let server = MockServer::start();
+        let metadata = json!({"blockNumber": 3456000, "blockHash": hash(3456000)});
+        server.on("getBlockNumber", json!({"data": 3456000, "metadata": null}));
+        server.on("getEpochNumber", json!({"data": 80, "metadata": null}));
+        server.on(
+            "getBlockByNumber",
+            json!({
+                "data": albatross_block(3456000, json!({
+                    "type": "macro",
+                    "isElectionBlock": true,
+                    "parentElectionHash": hash(3412800),
+                    "interlink": [hash(3412800)],
+                    "slots": [{
+                        "firstSlotNumber": 0,
+                        "numSlots": 512,
+                        "validator": ADDRESS,
+                        "publicKey": "aa".repeat(285)
+                    }],
+                    "lostRewardSet": [],
+                    "disabledSet": [],
+                    "justification": {"round": 0, "sig": {}}
+                })),
+                "metadata": null
+            }),
+        );
+        let mut micro = albatross_block(
+            3456001,
+            json!({
+                "type": "micro",
+                "producer": {"slotNumber": 17, "validator": ADDRESS, "publicKey": "bb".repeat(32)},
+                "justification": {"micro": "cc".repeat(64)}
+            }),
+        );
+        micro["transactions"] = json!([albatross_transaction(TX_HASH, 3456001)]);
+        server.on("getLatestBlock", json!({"data": micro, "metadata": null}));
+        server.on(
+            "getAccountByAddress",
+            json!({
+                "data": {
+                    "type": "vesting",
+                    "address": ADDRESS,
+                    "balance": 500,
+                    "owner": ADDRESS,
+                    "vestingStart": 1,
+                    "vestingTimeStep": 100,
+                    "vestingStepAmount": 10,
+                    "vestingTotalAmount": 1000
+                },
+                "metadata": metadata
+            }),
+        );
+        server.on(
+            "getTransactionsByAddress",
+            json!({"data": [albatross_transaction(TX_HASH, 3456001)], "metadata": null}),
+        );
+        server.on_error("getTransactionByHash", -32603, "Transaction not found");
+
+        let client = albatross::Client::new(&server.url());
+        assert_eq!(client.block_number().unwrap(), 3456000);
+        assert_eq!(client.epoch_number().unwrap(), 80);
+
+        let election = client.get_block_by_number(3456000, false).unwrap();
+        assert!(election.is_macro());
+        assert_eq!(election.epoch, 80);
+        match &election.kind {
+            albatross::primitives::BlockKind::Macro(block) => {
+                assert!(block.is_election_block);
+                assert_eq!(block.slots.as_ref().unwrap()[0].num_slots, 512);
+            }
+            kind => panic!("expected a macro block, got {:?}", kind),
+        }
+        assert!(election.transactions.is_none());
+
+        let latest = client.get_latest_block(true).unwrap();
+        assert!(!latest.is_macro());
+        match &latest.kind {
+            albatross::primitives::BlockKind::Micro(block) => {
+                assert_eq!(block.producer.slot_number, 17)
+            }
+            kind => panic!("expected a micro block, got {:?}", kind),
+        }
+        assert_eq!(latest.transactions.unwrap()[0].hash, TX_HASH);
+
+        let account = client.get_account_by_address(ADDRESS).unwrap();
+        assert_eq!(account.address(), ADDRESS);
+        assert_eq!(account.balance(), 500);
+        assert!(matches!(
+            account,
+            albatross::primitives::Account::Vesting {
+                vesting_total_amount: 1000,
+                ..
+            }
+        ));
+
+        let transactions = client
+            .get_transactions_by_address(ADDRESS, Some(10))
+            .unwrap();
+        assert_eq!(transactions[0].execution_result, Some(true));
+        assert_eq!(
+            server.requests_for("getTransactionsByAddress")[0].params,
+            vec![json!(ADDRESS), json!(10)]
+        );
+
+        match client.get_transaction_by_hash(TX_HASH) {
+            Err(jsonrpc::error::Error::Rpc(err)) => {
+                assert_eq!(err.message, "Transaction not found")
+            }
+            result => panic!("expected an RPC error, got {:?}", result),
+        }
+        let metrics = client.metrics().snapshot();
+        assert_eq!(metrics["getTransactionByHash"].errors, 1);
+        assert_eq!(metrics["getBlockNumber"].calls, 1);
+    }
+
+    #[test]
+    fn albatross_staking() {
+        use nimiq_rpc::albatross::primitives::*;
+
+        const VALIDATOR: &str = "NQ74 61S8 2FD3 RVPG HU09 1Y57 77E6 BL38 TQH2";
+        let validator = json!({
+            "address": VALIDATOR,
+            "signingKey": "aa".repeat(32),
+            "votingKey": "bb".repeat(285),
+            "rewardAddress": VALIDATOR,
+            "signalData": null,
+            "balance": 1_000_000_000_000u64,
+            "numStakers": 2,
+            "retired": false
+        });
+        let staker = json!({
+            "address": ADDRESS,
+            "balance": 500_000_000,
+            "delegation": VALIDATOR,
+            "inactiveBalance": 100_000_000,
+            "inactiveFrom": 3456000,
+            "retiredBalance": 0
+        });
+
+        let server = MockServer::start();
+        server.on(
+            "getActiveValidators",
+            json!({"data": [validator], "metadata": null}),
+        );
+        server.on(
+            "getStakersByValidatorAddress",
+            json!({"data": [staker], "metadata": null}),
+        );
+        server.on(
+            "getStakerByAddress",
+            json!({"data": staker, "metadata": {"blockNumber": 3456001, "blockHash": hash(1)}}),
+        );
+        server.on("getEpochAt", json!({"data": 80, "metadata": null}));
+        server.on(
+            "getElectionBlockOf",
+            json!({"data": 3456000, "metadata": null}),
+        );
+        server.on(
+            "createNewStakerTransaction",
+            json!({"data": "0100", "metadata": null}),
+        );
+        server.on(
+            "sendUpdateStakerTransaction",
+            json!({"data": TX_HASH, "metadata": null}),
+        );
+        server.on(
+            "sendSetActiveStakeTransaction",
+            json!({"data": TX_HASH, "metadata": null}),
+        );
+        server.on(
+            "createRetireStakeTransaction",
+            json!({"data": "0200", "metadata": null}),
+        );
+        server.on(
+            "sendRemoveStakeTransaction",
+            json!({"data": TX_HASH, "metadata": null}),
+        );
+        server.on(
+            "sendNewValidatorTransaction",
+            json!({"data": TX_HASH, "metadata": null}),
+        );
+        server.on(
+            "sendBasicTransaction",
+            json!({"data": TX_HASH, "metadata": null}),
+        );
+        let client = albatross::Client::new(&server.url());
+
+        let validators = client.get_active_validators().unwrap();
+        assert_eq!(validators[0].address, VALIDATOR);
+        assert_eq!(validators[0].jailed_from, None);
+        let stakers = client.get_stakers_by_validator_address(VALIDATOR).unwrap();
+        assert_eq!(stakers[0].delegation.as_deref(), Some(VALIDATOR));
+        let staker = client.get_staker_by_address(ADDRESS).unwrap();
+        assert_eq!(staker.total_balance(), 600_000_000);
+        assert_eq!(staker.inactive_from, Some(3456000));
+        assert_eq!(client.get_epoch_at(3456001).unwrap(), 80);
+        assert_eq!(client.get_election_block_of(80).unwrap(), 3456000);
+
+        let raw = client
+            .create_new_staker_transaction(&NewStakerTransaction {
+                sender_wallet: ADDRESS.to_owned(),
+                staker_wallet: ADDRESS.to_owned(),
+                delegation: Some(VALIDATOR.to_owned()),
+                value: 100_000_000,
+                fee: 0,
+                validity_start_height: ValidityStartHeight::Relative(0),
+            })
+            .unwrap();
+        assert_eq!(raw, "0100");
+        assert_eq!(
+            server.requests_for("createNewStakerTransaction")[0].params,
+            vec![
+                json!(ADDRESS),
+                json!(ADDRESS),
+                json!(VALIDATOR),
+                json!(100_000_000),
+                json!(0),
+                json!("+0")
+            ]
+        );
+
+        client
+            .send_update_staker_transaction(&UpdateStakerTransaction {
+                sender_wallet: None,
+                staker_wallet: ADDRESS.to_owned(),
+                new_delegation: None,
+                reactivate_all_stake: true,
+                fee: 0,
+                validity_start_height: ValidityStartHeight::Absolute(3456001),
+            })
+            .unwrap();
+        assert_eq!(
+            server.requests_for("sendUpdateStakerTransaction")[0].params,
+            vec![
+                Value::Null,
+                json!(ADDRESS),
+                Value::Null,
+                json!(true),
+                json!(0),
+                json!("3456001")
+            ]
+        );
+
+        // Unstaking deactivates, retires and then removes the stake.
+        let hash = client
+            .send_set_active_stake_transaction(&SetActiveStakeTransaction {
+                sender_wallet: ADDRESS.to_owned(),
+                staker_wallet: ADDRESS.to_owned(),
+                new_active_balance: 0,
+                fee: 0,
+                validity_start_height: ValidityStartHeight::Relative(0),
+            })
+            .unwrap();
+        assert_eq!(hash, TX_HASH);
+        assert_eq!(
+            server.requests_for("sendSetActiveStakeTransaction")[0].params,
+            vec![
+                json!(ADDRESS),
+                json!(ADDRESS),
+                json!(0),
+                json!(0),
+                json!("+0")
+            ]
+        );
+
+        let raw = client
+            .create_retire_stake_transaction(&RetireStakeTransaction {
+                sender_wallet: ADDRESS.to_owned(),
+                staker_wallet: ADDRESS.to_owned(),
+                retire_stake: 100_000_000,
+                fee: 0,
+                validity_start_height: ValidityStartHeight::Relative(0),
+            })
+            .unwrap();
+        assert_eq!(raw, "0200");
+        assert_eq!(
+            server.requests_for("createRetireStakeTransaction")[0].params,
+            vec![
+                json!(ADDRESS),
+                json!(ADDRESS),
+                json!(100_000_000),
+                json!(0),
+                json!("+0")
+            ]
+        );
+
+        let hash = client
+            .send_remove_stake_transaction(&RemoveStakeTransaction {
+                staker_wallet: ADDRESS.to_owned(),
+                recipient: ADDRESS.to_owned(),
+                value: 100_000_000,
+                fee: 0,
+                validity_start_height: ValidityStartHeight::default(),
+            })
+            .unwrap();
+        assert_eq!(hash, TX_HASH);
+
+        client
+            .send_new_validator_transaction(&NewValidatorTransaction {
+                sender_wallet: ADDRESS.to_owned(),
+                validator_wallet: VALIDATOR.to_owned(),
+                signing_secret_key: "00".repeat(32),
+                voting_secret_key: "00".repeat(95),
+                reward_address: VALIDATOR.to_owned(),
+                signal_data: String::new(),
+                fee: 0,
+                validity_start_height: ValidityStartHeight::Relative(5),
+            })
+            .unwrap();
+        let params = &server.requests_for("sendNewValidatorTransaction")[0].params;
+        assert_eq!(params.len(), 8);
+        assert_eq!(params[4], json!(VALIDATOR));
+        assert_eq!(params[7], json!("+5"));
+
+        client
+            .send_basic_transaction(ADDRESS, VALIDATOR, 1, 0, ValidityStartHeight::Relative(0))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_value::<ValidityStartHeight>(json!("+12")).unwrap(),
+            ValidityStartHeight::Relative(12)
+        );
+        assert!(serde_json::from_value::<ValidityStartHeight>(json!("twelve")).is_err());
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn websocket() {
+        use nimiq_rpc::albatross::primitives::BlockLog;
+        use nimiq_rpc::websocket::WebSocketTransport;
+        use std::net::TcpListener;
+        use tungstenite::Message;
+
+        // Answers requests and subscriptions, dropping the first connection right after its
+        // first notification.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (unsubscribed, unsubscriptions) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for (connection, stream) in listener.incoming().enumerate() {
+                let mut socket = tungstenite::accept(stream.unwrap()).unwrap();
+                while let Ok(message) = socket.read() {
+                    let request: Value = match message {
+                        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                        _ => continue,
+                    };
+                    let reply = |result: Value| {
+                        Message::Text(
+                            json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+                                .to_string(),
+                        )
+                    };
+                    let notify = |subscription: u64, result: Value| {
+                        Message::Text(
+                            json!({
+                                "jsonrpc": "2.0",
+                                "method": request["method"],
+                                "params": {"subscription": subscription, "result": result}
+                            })
+                            .to_string(),
+                        )
+                    };
+                    match request["method"].as_str().unwrap() {
+                        "getBlockNumber" => socket
+                            .send(reply(json!({"data": 42, "metadata": null})))
+                            .unwrap(),
+                        "subscribeForHeadBlockHash" => {
+                            let subscription = 7 + connection as u64;
+                            socket.send(reply(json!(subscription))).unwrap();
+                            let head =
+                                json!({"data": hash(connection as u64 + 1), "metadata": null});
+                            socket.send(notify(subscription, head)).unwrap();
+                            if connection == 0 {
+                                break;
+                            }
+                        }
+                        "subscribeForLogsByAddressesAndTypes" => {
+                            assert_eq!(request["params"], json!([[ADDRESS], []]));
+                            socket.send(reply(json!(20))).unwrap();
+                            let log = json!({
+                                "type": "applied-block",
+                                "inherents": [],
+                                "blockHash": hash(3),
+                                "blockNumber": 3456002,
+                                "timestamp": 1704067200000u64,
+                                "transactions": [{"hash": TX_HASH, "logs": [], "failed": false}]
+                            });
+                            socket.send(notify(20, log)).unwrap();
+                        }
+                        "unsubscribe" => {
+                            unsubscribed.send(request["params"].clone()).unwrap();
+                            socket.send(reply(json!(true))).unwrap();
+                        }
+                        method => panic!("unexpected method {}", method),
+                    }
+                }
+            }
+        });
+
+        assert!(WebSocketTransport::new("wss://seed-host.com:8648/ws").is_err());
+        assert!(WebSocketTransport::new("seed-host.com:8648").is_err());
+        let transport = WebSocketTransport::new(&url)
+            .unwrap()
+            .timeout(Duration::from_secs(5))
+            .reconnect_delay(Duration::from_millis(10));
+        let client = albatross::Client::new_with_transport(transport.clone());
+        assert_eq!(client.block_number().unwrap(), 42);
+        assert!(transport.is_connected());
+
+        let heads = transport.subscribe_head_hashes().unwrap();
+        let timeout = Duration::from_secs(5);
+        assert_eq!(heads.next_timeout(timeout).unwrap().unwrap(), hash(1));
+        // The subscription is renewed on the new connection.
+        assert_eq!(heads.next_timeout(timeout).unwrap().unwrap(), hash(2));
+        assert_eq!(transport.reconnects(), 1);
+        drop(heads);
+        assert_eq!(unsubscriptions.recv_timeout(timeout).unwrap(), json!([8]));
+
+        let mut logs = transport.subscribe_logs(&[ADDRESS], &[]).unwrap();
+        match logs.next().unwrap().unwrap() {
+            log @ BlockLog::AppliedBlock {
+                block_number: 3456002,
+                ..
+            } => {
+                assert_eq!(log.transactions()[0].hash, TX_HASH)
+            }
+            log => panic!("unexpected log {:?}", log),
+        }
+        assert_eq!(client.block_number().unwrap(), 42);
+
+        transport.close();
+        assert!(logs.next().is_none());
+        assert!(client.block_number().is_err());
+    }
+
+    #[test]
+    fn protocol_detection() {
+        use nimiq_rpc::protocol::{Capability, NodeInfo, Protocol};
+
+        let legacy = MockServer::start();
+        legacy.on("blockNumber", 882418);
+        let client = legacy.client();
+        assert_eq!(client.supports("getBlockNumber"), None);
+        let info = client.node_info().unwrap();
+        assert_eq!(
+            info,
+            NodeInfo {
+                protocol: Protocol::Legacy,
+                version: None
+            }
+        );
+        assert!(info.supports(Capability::ProofOfWork));
+        assert_eq!(client.supports("getBlockNumber"), Some(false));
+        assert_eq!(client.supports("blockNumber"), Some(true));
+        // The result is cached.
+        client.node_info().unwrap();
+        assert_eq!(legacy.requests_for("blockNumber").len(), 1);
+        client.require(Capability::ProofOfWork).unwrap();
+        let err = client.require(Capability::ProofOfStake).unwrap_err();
+        assert_eq!(
+            protocol::Unsupported::from_error(&err),
+            Some(&protocol::Unsupported {
+                protocol: Protocol::Legacy,
+                capability: Capability::ProofOfStake
+            })
+        );
+        assert!(!protocol::is_method_not_found(&err));
+        assert!(!transport::is_transport_error(&err));
+        assert!(err
+            .to_string()
+            .contains("Nimiq 1.x nodes do not support proof of stake"));
+
+        let albatross = MockServer::start();
+        albatross
+            .on("getBlockNumber", json!({"data": 3456000, "metadata": null}))
+            .on(
+                "getPolicyConstants",
+                json!({"data": {"version": 1}, "metadata": null}),
+            );
+        let client = albatross::Client::new(&albatross.url());
+        let info = client.node_info().unwrap();
+        assert_eq!(info.protocol, Protocol::Albatross);
+        assert_eq!(info.version, Some(1));
+        client.require(Capability::ProofOfStake).unwrap();
+        let err = client.require(Capability::ProofOfWork).unwrap_err();
+        assert_eq!(
+            protocol::Unsupported::from_error(&err).map(|err| err.capability),
+            Some(Capability::ProofOfWork)
+        );
+        assert!(albatross.requests_for("blockNumber").is_empty());
+
+        #[cfg(feature = "mining")]
+        {
+            let client = Client::new(&albatross.url());
+            let miner = mining::Miner::new(&client);
+            assert!(protocol::Unsupported::from_error(&miner.mine_block().unwrap_err()).is_some());
+            assert!(albatross.requests_for("getWork").is_empty());
+        }
+
+        let unknown = MockServer::start();
+        let client = unknown.client();
+        assert!(protocol::is_method_not_found(
+            &client.node_info().unwrap_err()
+        ));
+        unknown.on("blockNumber", 1);
+        assert_eq!(client.probe().unwrap().protocol, Protocol::Legacy);
+    }
+
+    #[test]
+    fn rate_limit() {
+        use nimiq_rpc::ratelimit::{self, Limit, RateLimiter};
+        use nimiq_rpc::transport::Transport;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Answers every request after a delay and tracks how many were served at once.
+        #[derive(Default)]
+        struct Slow {
+            in_flight: AtomicUsize,
+            most: AtomicUsize,
+        }
+
+        impl Transport for Slow {
+            fn send_request(
+                &self,
+                request: &jsonrpc::Request,
+            ) -> Result<jsonrpc::Response, jsonrpc::error::Error> {
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.most.fetch_max(in_flight, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(serde_json::from_value(json!({
+                    "result": 1,
+                    "error": null,
+                    "id": request.id,
+                    "jsonrpc": "2.0"
+                }))
+                .unwrap())
+            }
+        }
+
+        let server = MockServer::start();
+        server.on("blockNumber", 882418);
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let client =
+            Client::new_with_transport(RateLimiter::new(agent, Limit::per_second(50.0).burst(2)));
+        let start = Instant::now();
+        for _ in 0..6 {
+            assert_eq!(client.block_number().unwrap(), 882418);
+        }
+        // Two requests pass at once, the other four are 20 ms apart.
+        assert!(start.elapsed() >= Duration::from_millis(75));
+
+        let slow = Arc::new(Slow::default());
+        let client = Arc::new(Client::new_with_transport(
+            RateLimiter::new(Arc::clone(&slow), Limit::unlimited().max_in_flight(3))
+                .class(ratelimit::is_heavy, Limit::unlimited().max_in_flight(1)),
+        ));
+        let threads = (0..4)
+            .map(|_| {
+                let client = Arc::clone(&client);
+                std::thread::spawn(move || client.get_block_transaction_count_by_number(1))
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap().unwrap();
+        }
+        assert_eq!(slow.most.load(Ordering::SeqCst), 3);
+
+        slow.most.store(0, Ordering::SeqCst);
+        let threads = (0..3)
+            .map(|_| {
+                let client = Arc::clone(&client);
+                std::thread::spawn(move || client.get_block_by_number(1, true).map(|_| ()))
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            // The canned result is not a block, only the concurrency matters here.
+            let _ = thread.join().unwrap();
+        }
+        assert_eq!(slow.most.load(Ordering::SeqCst), 1);
+
+        let request = |method, params: &[Value]| {
+            ratelimit::is_heavy(&jsonrpc::Request {
+                method,
+                params,
+                id: json!(1),
+                jsonrpc: Some("2.0"),
+            })
+        };
+        assert!(request(
+            "getTransactionsByAddress",
+            &[json!(ADDRESS), json!(10)]
+        ));
+        assert!(request("getBlockByHash", &[json!(BLOCK_HASH), json!(true)]));
+        assert!(!request("getBlockByNumber", &[json!(1), json!(false)]));
+        assert!(!request("blockNumber", &[]));
+    }
+
+    #[test]
+    fn response_cache() {
+        use nimiq_rpc::cache::ResponseCache;
+
+        let server = MockServer::start();
+        server
+            .on("blockNumber", 100)
+            .on("getBalance", 1000)
+            .on("getBlockByHash", block(50, BLOCK_HASH, &hash(49)))
+            .on_call("getBlockByNumber", |params| {
+                let number = params[0].as_u64().unwrap();
+                Ok(block(number, &hash(number), &hash(number - 1)))
+            })
+            .on_call("getTransactionByHash", |params| {
+                let number = if params[0] == TX_HASH { 60 } else { 98 };
+                let mut tx = transaction(
+                    params[0].as_str().unwrap(),
+                    &block(number, &hash(number), &hash(number - 1)),
+                    0,
+                    ADDRESS,
+                    OTHER_ADDRESS,
+                );
+                tx["confirmations"] = json!(100 - number + 1);
+                Ok(tx)
+            });
+
+        let cassette =
+            std::env::temp_dir().join(format!("nimiq-rpc-cache-{}.json", std::process::id()));
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let cache = Arc::new(ResponseCache::new(agent, 3).persist(&cassette).unwrap());
+        let client = Client::new_with_transport(Arc::clone(&cache));
+
+        for _ in 0..2 {
+            client.get_block_by_hash(BLOCK_HASH, false).unwrap();
+            client.get_block_by_number(90, false).unwrap();
+            client.get_block_by_number(95, false).unwrap();
+            client.get_balance(ADDRESS).unwrap();
+            client.get_transaction_by_hash(&hash(1)).unwrap();
+        }
+        assert_eq!(server.requests_for("getBlockByHash").len(), 1);
+        assert_eq!(server.requests_for("getBlockByNumber").len(), 3);
+        assert_eq!(server.requests_for("getBalance").len(), 2);
+        // Block 95 and the transaction in block 98 are too recent to be cached.
+        assert_eq!(server.requests_for("getTransactionByHash").len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (2, 6));
+        assert_eq!(cache.len(), 2);
+
+        // The transaction in block 60 is cached and its confirmations follow the head.
+        assert_eq!(
+            client
+                .get_transaction_by_hash(TX_HASH)
+                .unwrap()
+                .confirmations,
+            41
+        );
+        server.on("blockNumber", 120);
+        client.block_number().unwrap();
+        assert_eq!(
+            client
+                .get_transaction_by_hash(TX_HASH)
+                .unwrap()
+                .confirmations,
+            61
+        );
+        assert_eq!(server.requests_for("getTransactionByHash").len(), 3);
+
+        // The least recently used result was evicted.
+        client.get_block_by_number(91, false).unwrap();
+        assert_eq!(cache.len(), 3);
+        client.get_block_by_hash(BLOCK_HASH, false).unwrap();
+        assert_eq!(server.requests_for("getBlockByHash").len(), 2);
+
+        drop(client);
+        drop(cache);
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let cache = ResponseCache::new(agent, 3).persist(&cassette).unwrap();
+        assert_eq!(cache.len(), 3);
+        let client = Client::new_with_transport(cache);
+        client.get_block_by_number(91, false).unwrap();
+        assert_eq!(server.requests_for("getBlockByNumber").len(), 4);
+        std::fs::remove_file(cassette).unwrap();
+    }
+
+    #[test]
+    fn response_cache_block_transactions() {
+        use nimiq_rpc::cache::ResponseCache;
+        use nimiq_rpc::primitives::TransactionSequence;
+
+        let server = MockServer::start();
+        let mut block = block(50, BLOCK_HASH, &hash(49));
+        let mut tx = transaction(TX_HASH, &block, 0, ADDRESS, OTHER_ADDRESS);
+        tx["confirmations"] = json!(51);
+        block["transactions"] = json!([tx]);
+        server.on("blockNumber", 100).on("getBlockByHash", block);
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let client = Client::new_with_transport(ResponseCache::new(agent, 10));
+        let confirmations = || match client
+            .get_block_by_hash(BLOCK_HASH, true)
+            .unwrap()
+            .transactions
+        {
+            TransactionSequence::Transactions(transactions) => transactions[0].confirmations,
+            transactions => panic!("unexpected transactions {:?}", transactions),
+        };
+
+        assert_eq!(confirmations(), 51);
+        // The transactions of the cached block follow the head.
+        server.on("blockNumber", 120);
+        client.block_number().unwrap();
+        assert_eq!(confirmations(), 71);
+        assert_eq!(server.requests_for("getBlockByHash").len(), 1);
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        use nimiq_rpc::breaker::{CircuitBreaker, CircuitOpen, CircuitState};
+
+        let server = MockServer::start();
+        server
+            .on_disconnect("blockNumber")
+            .on_disconnect("consensus");
+        let agent = jsonrpc::client::Client::new(server.url(), None, None);
+        let breaker = Arc::new(
+            CircuitBreaker::new(agent)
+                .threshold(2)
+                .cooldown(Duration::from_millis(50)),
+        );
+        let client = Client::new_with_transport(Arc::clone(&breaker));
+
+        for _ in 0..2 {
+            let err = client.block_number().unwrap_err();
+            assert!(CircuitOpen::from_error(&err).is_none());
+            assert!(transport::is_transport_error(&err));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.failures(), 2);
+        let err = client.block_number().unwrap_err();
+        let open = CircuitOpen::from_error(&err).unwrap();
+        assert!(!transport::is_transport_error(&err));
+        assert_eq!(open.failures, 2);
+        assert!(open.retry_in <= Duration::from_millis(50));
+        assert_eq!(server.requests_for("blockNumber").len(), 2);
+
+        // A failing probe opens the circuit again without sending the request.
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(client.block_number().is_err());
+        assert_eq!(server.requests_for("consensus").len(), 1);
+        assert_eq!(server.requests_for("blockNumber").len(), 2);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        server
+            .on("blockNumber", 882418)
+            .on("consensus", "established");
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(client.block_number().unwrap(), 882418);
+        assert_eq!(server.requests_for("consensus").len(), 2);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.failures(), 0);
+
+        // Error responses mean the node is up.
+        server.on_error("blockNumber", -32603, "Internal error");
+        for _ in 0..3 {
+            assert!(client.block_number().is_err());
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unix_socket() {
+        use nimiq_rpc::unix::UnixSocketTransport;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("nimiq-rpc-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (i, stream) in listener.incoming().take(2).enumerate() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut head = Vec::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_owned();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    head.push(line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let response =
+                    json!({"jsonrpc": "2.0", "id": request["id"], "result": 882418}).to_string();
+
+                let mut stream = reader.into_inner();
+                if i == 0 {
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        response.len(),
+                        response
+                    )
+                    .unwrap();
+                } else {
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                        response.len(),
+                        response
+                    )
+                    .unwrap();
+                }
+                requests.push((head, request));
+            }
+            requests
+        });
+
+        let client = Client::new_with_unix_socket(&path);
+        assert_eq!(client.block_number().unwrap(), 882418);
+        let client = Client::new_with_transport(
+            UnixSocketTransport::new(&path)
+                .credentials("user", "secret")
+                .timeout(Duration::from_secs(5)),
+        );
+        assert_eq!(client.block_number().unwrap(), 882418);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].0[0], "POST / HTTP/1.1");
+        assert!(requests[0]
+            .0
+            .iter()
+            .any(|line| line == "Content-Type: application/json"));
+        assert!(!requests[0]
+            .0
+            .iter()
+            .any(|line| line.starts_with("Authorization")));
+        assert_eq!(requests[0].1["method"], "blockNumber");
+        // "user:secret" in base64.
+        assert!(requests[1]
+            .0
+            .iter()
+            .any(|line| line == "Authorization: Basic dXNlcjpzZWNyZXQ="));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(client.block_number().is_err());
     }
 }